@@ -12,8 +12,11 @@ use rmcp::{tool_handler, tool_router, schemars, ErrorData as McpError, ServerHan
 use serde::{Deserialize, Serialize};
 use tokio::sync::Mutex;
 
-use recall_lite_lib::config::{get_embedding_model, get_table_name, load_config, Config};
+use recall_lite_lib::config::{get_embedding_model, get_table_name, load_config, Config, FusionStrategy};
 use recall_lite_lib::indexer;
+use recall_lite_lib::indexer::filter::Filter;
+use recall_lite_lib::indexer::paths::PathMap;
+use recall_lite_lib::indexer::report::{reports_dir, IndexReport};
 
 #[global_allocator]
 static GLOBAL: MiMalloc = MiMalloc;
@@ -21,12 +24,36 @@ static GLOBAL: MiMalloc = MiMalloc;
 struct Models {
     model: fastembed::TextEmbedding,
     reranker: Option<fastembed::TextRerank>,
+    /// The model a table searched last was built with, when not `model`.
+    legacy: Option<indexer::embedding::LegacyModel>,
 }
 
 struct AppState {
     db: lancedb::Connection,
     models: Arc<Mutex<Models>>,
-    config: Config,
+    /// Model loaded at startup; tables built with another one are searched with theirs,
+    /// loaded on demand, until the GUI has re-embedded them.
+    identity: indexer::embedding::ModelIdentity,
+    models_path: PathBuf,
+    config_path: PathBuf,
+}
+
+impl AppState {
+    /// Re-read on every call: the GUI edits containers and folders while this server runs.
+    fn config(&self) -> Config {
+        load_config(&self.config_path)
+    }
+
+    /// Resolves the paths stored in `container`'s table against its current roots.
+    fn paths(&self, container: &str) -> PathMap {
+        let roots = self
+            .config()
+            .containers
+            .get(container)
+            .map(|info| info.indexed_paths.clone())
+            .unwrap_or_default();
+        PathMap::new(roots)
+    }
 }
 
 #[derive(Clone)]
@@ -37,25 +64,41 @@ pub struct RecallServer {
 
 #[derive(Deserialize, schemars::JsonSchema)]
 struct SearchParams {
+    #[schemars(description = "Search text. May carry filters inline: ext:rs, in:src/indexer, after:2025-01-01, before:2025-01-31, \"exact phrase\", -excluded")]
     query: String,
     container: Option<String>,
+    #[schemars(description = "Search several containers at once, e.g. [\"work\", \"wiki\"]. Replaces container; fusion settings come from the first one. Containers indexed with another model than the first are left out and listed after the results")]
+    containers: Option<Vec<String>>,
     #[schemars(description = "Number of results to return (default 10, max 50)")]
     top_k: Option<usize>,
     #[schemars(description = "Filter by file extensions, e.g. [\"rs\", \"ts\", \"py\"]")]
     file_extensions: Option<Vec<String>>,
     #[schemars(description = "Filter by path prefix, e.g. \"src/indexer\"")]
     path_prefix: Option<String>,
+    #[schemars(description = "Only files modified within this window: 'last 7d', '2h', 'today', 'yesterday', '2025-01-31', '2025-01-01..2025-01-31', '2025-01-01..'")]
+    modified: Option<String>,
     #[schemars(description = "Max snippet size in bytes (default 1500, max 10000)")]
     context_bytes: Option<usize>,
     #[schemars(description = "Minimum relevance score 0-100 to include in results (default 0)")]
     min_score: Option<f32>,
+    #[schemars(description = "How vector and keyword results are combined: 'rrf', 'convex', 'vector_only' or 'fts_only'. Defaults to the container's setting.")]
+    fusion: Option<String>,
+    #[schemars(description = "Weight of the vector results in 'rrf' and 'convex' fusion (default from the container, usually 1.0)")]
+    vector_weight: Option<f32>,
+    #[schemars(description = "Weight of the keyword results in 'rrf' and 'convex' fusion (default from the container, usually 1.0)")]
+    fts_weight: Option<f32>,
+    #[schemars(description = "Return only the best chunk of each file (default true). Set false to get every matching chunk, several per file")]
+    per_file: Option<bool>,
 }
 
 #[derive(Serialize)]
 struct SearchResultItem {
+    container: String,
     path: String,
     snippet: String,
     score: f32,
+    #[serde(flatten)]
+    meta: indexer::db::ChunkMeta,
 }
 
 #[derive(Deserialize, schemars::JsonSchema)]
@@ -106,8 +149,8 @@ fn is_path_within_container(file_path: &Path, config: &Config, container_name: &
         Err(_) => return false,
     };
     if let Some(info) = config.containers.get(container_name) {
-        for indexed_path in &info.indexed_paths {
-            if let Ok(indexed_canonical) = std::fs::canonicalize(indexed_path) {
+        for root in &info.indexed_paths {
+            if let Ok(indexed_canonical) = std::fs::canonicalize(&root.path) {
                 if canonical.starts_with(&indexed_canonical) {
                     return true;
                 }
@@ -117,24 +160,6 @@ fn is_path_within_container(file_path: &Path, config: &Config, container_name: &
     false
 }
 
-fn parse_duration(s: &str) -> Option<u64> {
-    let s = s.trim().to_lowercase();
-    let (num_str, multiplier) = if let Some(n) = s.strip_suffix('s') {
-        (n, 1u64)
-    } else if let Some(n) = s.strip_suffix('m') {
-        (n, 60)
-    } else if let Some(n) = s.strip_suffix('h') {
-        (n, 3600)
-    } else if let Some(n) = s.strip_suffix('d') {
-        (n, 86400)
-    } else if let Some(n) = s.strip_suffix('w') {
-        (n, 604800)
-    } else {
-        return None;
-    };
-    num_str.trim().parse::<u64>().ok().map(|n| n * multiplier)
-}
-
 #[tool_router]
 impl RecallServer {
     fn new(state: Arc<AppState>) -> Self {
@@ -145,81 +170,148 @@ impl RecallServer {
     }
 
     #[tool(
-        description = "Search indexed files using semantic + keyword hybrid search. Returns ranked results with file paths, relevant snippets, and relevance scores."
+        description = "Search indexed files using semantic + keyword hybrid search. Returns ranked results with file paths, relevant snippets, relevance scores, and the line range of each matching chunk."
     )]
     async fn recall_search(
         &self,
-        Parameters(SearchParams { query, container, top_k, file_extensions, path_prefix, context_bytes, min_score }): Parameters<SearchParams>,
+        Parameters(SearchParams { query, container, containers, top_k, file_extensions, path_prefix, modified, context_bytes, min_score, fusion, vector_weight, fts_weight, per_file }): Parameters<SearchParams>,
     ) -> Result<CallToolResult, McpError> {
-        let container =
-            container.unwrap_or_else(|| self.state.config.active_container.clone());
-        let table_name = get_table_name(&container);
+        let config = self.state.config();
+        let names = match containers.filter(|c| !c.is_empty()) {
+            Some(mut names) => {
+                let mut seen = std::collections::HashSet::new();
+                names.retain(|name| seen.insert(name.clone()));
+                names
+            }
+            None => vec![container.unwrap_or_else(|| config.active_container.clone())],
+        };
+        // Explicit parameters add to the filters typed in the query; the prefix and the
+        // time window replace theirs
+        let mut parsed = indexer::parse_query(&query);
+        if let Some(prefix) = path_prefix {
+            parsed.filters.path_prefixes = vec![prefix];
+        }
+        parsed.filters.extensions.extend(file_extensions.unwrap_or_default());
+        if let Some(spec) = modified {
+            let window = indexer::query::TimeWindow::parse(&spec, chrono::Local::now()).ok_or_else(|| {
+                McpError::invalid_params(format!("invalid time window '{}'. use 'last 7d', 'today' or '2025-01-01..2025-01-31'", spec), None)
+            })?;
+            parsed.filters.modified = Some(window);
+        }
+        if parsed.text.is_empty() {
+            return Ok(CallToolResult::success(vec![Content::text(
+                "the query has no search terms besides its filters.",
+            )]));
+        }
+        let mut fusion_config = config
+            .containers
+            .get(&names[0])
+            .map(|info| info.fusion)
+            .unwrap_or_default();
+        if let Some(name) = fusion {
+            fusion_config.strategy = FusionStrategy::from_name(&name).ok_or_else(|| {
+                McpError::invalid_params(format!("unknown fusion '{}'. use 'rrf', 'convex', 'vector_only' or 'fts_only'", name), None)
+            })?;
+        }
+        for weight in [vector_weight, fts_weight].into_iter().flatten() {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(McpError::invalid_params("weights must be non-negative numbers", None));
+            }
+        }
+        fusion_config.vector_weight = vector_weight.unwrap_or(fusion_config.vector_weight);
+        fusion_config.fts_weight = fts_weight.unwrap_or(fusion_config.fts_weight);
 
         let top_k = top_k.unwrap_or(10).min(50).max(1);
         let context_bytes = context_bytes.unwrap_or(1500).min(10000).max(100);
 
         let table_check = self.state.db.table_names().execute().await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-        if !table_check.iter().any(|t| t == &table_name) {
-            return Ok(CallToolResult::success(vec![Content::text(
-                format!("no index found for container '{}'. open Recall Lite and index some folders first.", container),
-            )]));
+        let mut targets = Vec::new();
+        let mut paths = std::collections::HashMap::new();
+        let mut indexed = None;
+        let mut skipped = Vec::new();
+        for (i, container) in names.iter().enumerate() {
+            let table_name = get_table_name(container);
+            if !table_check.iter().any(|t| t == &table_name) {
+                return Ok(CallToolResult::success(vec![Content::text(
+                    format!("no index found for container '{}'. open Recall Lite and index some folders first.", container),
+                )]));
+            }
+            let mismatch = indexer::schema::model_mismatch(&self.state.db, &table_name, &self.state.identity)
+                .await
+                .map_err(|e| McpError::internal_error(e.to_string(), None))?;
+            if i == 0 {
+                indexed = mismatch;
+            } else if mismatch != indexed {
+                // One query vector serves every table: search the first container without it
+                skipped.push(container.clone());
+                continue;
+            }
+            let info = config.containers.get(container);
+            let container_paths = PathMap::new(info.map(|i| i.indexed_paths.clone()).unwrap_or_default());
+            let mut filters = parsed.filters.clone();
+            filters.path_prefixes =
+                filters.path_prefixes.iter().flat_map(|p| container_paths.stored_prefixes(p)).collect();
+            targets.push(indexer::SearchTarget {
+                container: container.clone(),
+                table: table_name,
+                filters,
+                vectors: info.map(|i| i.vectors.clone()).unwrap_or_default(),
+            });
+            paths.insert(container.clone(), container_paths);
         }
 
         let query_vector = {
             let mut guard = self.state.models.lock().await;
-            indexer::embed_query(&mut guard.model, &query)
-                .map_err(|e| McpError::internal_error(e.to_string(), None))?
-        };
-
-        let search_limit = top_k * 3;
-
-        let pp_ref = path_prefix.as_deref();
-        let fe_ref = file_extensions.as_deref();
-
-        let query_variants = indexer::expand_query(&query);
-        let vector_fut =
-            indexer::search_files(&self.state.db, &table_name, &query_vector, search_limit, pp_ref, fe_ref, false);
-
-        let fts_db = self.state.db.clone();
-        let fts_table = table_name.clone();
-        let fe_clone = file_extensions.clone();
-        let pp_clone = path_prefix.clone();
-        let fts_fut = async move {
-            let pp_ref2 = pp_clone.as_deref();
-            let fe_ref2 = fe_clone.as_deref();
-            let futs: Vec<_> = query_variants
-                .iter()
-                .map(|v| indexer::search_fts(&fts_db, &fts_table, v, 30, pp_ref2, fe_ref2, false))
-                .collect();
-            let results = futures::future::join_all(futs).await;
-            let mut all: Vec<(String, String)> = Vec::new();
-            let mut seen = std::collections::HashSet::new();
-            for result in results.into_iter().flatten() {
-                for item in result {
-                    if seen.insert(item.0.clone()) {
-                        all.push(item);
-                    }
+            match indexed {
+                // Still on its previous model, e.g. while the GUI re-embeds it
+                Some(indexed) => {
+                    let legacy = match guard.legacy.take().filter(|l| l.identity == indexed) {
+                        Some(legacy) => legacy,
+                        None => {
+                            let identity = indexed.clone();
+                            let cache_dir = self.state.models_path.clone();
+                            let loaded = tokio::task::spawn_blocking(move || {
+                                indexer::embedding::LegacyModel::load(identity, cache_dir)
+                            })
+                            .await;
+                            match loaded {
+                                Ok(Ok(legacy)) => legacy,
+                                _ => {
+                                    let e = indexer::schema::mismatch_error(&indexed, &self.state.identity);
+                                    return Ok(CallToolResult::success(vec![Content::text(
+                                        format!("cannot search container '{}': {}", names[0], e),
+                                    )]));
+                                }
+                            }
+                        }
+                    };
+                    let legacy = guard.legacy.insert(legacy);
+                    legacy
+                        .embed_query(&parsed.text)
+                        .map_err(|e| McpError::internal_error(e.to_string(), None))?
                 }
+                None => indexer::embed_query(&mut guard.model, &parsed.text)
+                    .map_err(|e| McpError::internal_error(e.to_string(), None))?,
             }
-            all
         };
 
-        let (vector_result, fts_results) = tokio::join!(vector_fut, fts_fut);
-        let vector_results = vector_result
-            .map_err(|e| McpError::internal_error(e.to_string(), None))?;
-
-        let merged = if fts_results.is_empty() {
-            vector_results
-        } else {
-            indexer::hybrid_merge(&vector_results, &fts_results, search_limit)
-        };
+        let search_limit = top_k * 3;
+        let (merged, used_hybrid) = indexer::search_pipeline(
+            &self.state.db,
+            &targets,
+            &parsed.text,
+            &query_vector,
+            search_limit,
+            &fusion_config,
+            per_file.unwrap_or(true),
+        )
+        .await
+        .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
-        let rerank_input: Vec<(String, String, f32)> =
+        let rerank_input: Vec<indexer::SearchHit> =
             merged.into_iter().take(top_k * 2).collect();
 
-        let used_hybrid = !fts_results.is_empty();
-
         let (final_results, used_reranker) = {
             let reranker_opt = {
                 let mut guard = self.state.models.lock().await;
@@ -227,7 +319,7 @@ impl RecallServer {
             };
             if let Some(reranker) = reranker_opt {
                 let (reranker_back, results, used) =
-                    indexer::safe_rerank(reranker, query.clone(), rerank_input).await;
+                    indexer::safe_rerank(reranker, parsed.text.clone(), rerank_input).await;
                 {
                     let mut guard = self.state.models.lock().await;
                     if let Some(r) = reranker_back {
@@ -245,7 +337,13 @@ impl RecallServer {
             indexer::pipeline::score_results(final_results, used_reranker, used_hybrid, top_k)
                 .into_iter()
                 .filter(|r| r.score >= min_score)
-                .map(|r| SearchResultItem { path: r.path, snippet: r.snippet, score: r.score })
+                .map(|r| SearchResultItem {
+                    path: paths.get(&r.container).map_or_else(|| r.path.clone(), |p| p.resolve(&r.path)),
+                    container: r.container,
+                    snippet: r.snippet,
+                    score: r.score,
+                    meta: r.meta,
+                })
                 .collect();
 
         for item in &mut scored {
@@ -258,7 +356,15 @@ impl RecallServer {
         let json = serde_json::to_string_pretty(&scored)
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
-        Ok(CallToolResult::success(vec![Content::text(json)]))
+        let mut content = vec![Content::text(json)];
+        if !skipped.is_empty() {
+            content.push(Content::text(format!(
+                "not searched: {} indexed with a different model than '{}'. search them on their own.",
+                skipped.join(", "),
+                names[0]
+            )));
+        }
+        Ok(CallToolResult::success(content))
     }
 
     #[tool(
@@ -271,8 +377,9 @@ impl RecallServer {
         let file_path = PathBuf::from(&path);
 
         let mut authorized = false;
-        for (name, _) in &self.state.config.containers {
-            if is_path_within_container(&file_path, &self.state.config, name) {
+        let config = self.state.config();
+        for name in config.containers.keys() {
+            if is_path_within_container(&file_path, &config, name) {
                 authorized = true;
                 break;
             }
@@ -344,8 +451,7 @@ impl RecallServer {
         use futures::TryStreamExt;
         use lancedb::query::{ExecutableQuery, QueryBase};
 
-        let container =
-            container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let container = container.unwrap_or_else(|| self.state.config().active_container);
         let table_name = get_table_name(&container);
 
         let table = match self.state.db.open_table(&table_name).execute().await {
@@ -359,8 +465,14 @@ impl RecallServer {
 
         let mut query = table.query().select(lancedb::query::Select::Columns(vec!["path".to_string()]));
 
-        if let Some(filter) = indexer::build_filter_expr(path_prefix.as_deref(), extensions.as_deref()) {
-            query = query.only_if(filter);
+        let paths = self.state.paths(&container);
+        let filters = indexer::SearchFilters {
+            path_prefixes: path_prefix.map(|p| paths.stored_prefixes(&p)).unwrap_or_default(),
+            extensions: extensions.unwrap_or_default(),
+            ..Default::default()
+        };
+        if let Some(filter) = filters.to_filter() {
+            query = query.only_if(filter.to_sql());
         }
 
         let results = query
@@ -371,19 +483,19 @@ impl RecallServer {
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
-        let mut paths = std::collections::BTreeSet::new();
+        let mut files = std::collections::BTreeSet::new();
         for batch in results {
             if let Some(path_array) = batch
                 .column_by_name("path")
                 .and_then(|c| c.as_any().downcast_ref::<StringArray>())
             {
                 for i in 0..batch.num_rows() {
-                    paths.insert(path_array.value(i).to_string());
+                    files.insert(paths.resolve(path_array.value(i)));
                 }
             }
         }
 
-        let file_list: Vec<serde_json::Value> = paths
+        let file_list: Vec<serde_json::Value> = files
             .iter()
             .map(|p| {
                 let size = std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
@@ -405,7 +517,7 @@ impl RecallServer {
     }
 
     #[tool(
-        description = "Get index status: total files, total chunks, and container metadata. Use this to check if the index is populated before searching."
+        description = "Get index status: total files, total chunks, container metadata, and the last indexing run (counts, skipped and failed files with reasons, timings), plus fragment and unindexed row counts and the embedding model the index was built with. Use this to check if the index is populated before searching."
     )]
     async fn recall_index_status(
         &self,
@@ -415,17 +527,19 @@ impl RecallServer {
         use futures::TryStreamExt;
         use lancedb::query::{ExecutableQuery, QueryBase};
 
-        let container =
-            container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let config = self.state.config();
+        let container = container.unwrap_or_else(|| config.active_container.clone());
         let table_name = get_table_name(&container);
 
-        let container_info = self.state.config.containers.get(&container);
+        let container_info = config.containers.get(&container);
         let indexed_paths: Vec<String> = container_info
-            .map(|info| info.indexed_paths.clone())
+            .map(|info| info.root_paths())
             .unwrap_or_default();
         let description = container_info
             .map(|info| info.description.clone())
             .unwrap_or_default();
+        let last_run = IndexReport::load(&reports_dir(&self.state.config_path), &container)
+            .map(|report| last_run_json(&report));
 
         let table = match self.state.db.open_table(&table_name).execute().await {
             Ok(t) => t,
@@ -437,6 +551,7 @@ impl RecallServer {
                     "total_files": 0,
                     "total_chunks": 0,
                     "has_index": false,
+                    "last_run": last_run,
                 }))
                 .map_err(|e| McpError::internal_error(e.to_string(), None))?;
                 return Ok(CallToolResult::success(vec![Content::text(json)]));
@@ -468,6 +583,14 @@ impl RecallServer {
             }
         }
 
+        let indexed_model = indexer::schema::get_model(&self.state.db, &table_name)
+            .await
+            .ok()
+            .flatten();
+        let model_mismatch = indexed_model
+            .as_ref()
+            .is_some_and(|indexed| indexed != &self.state.identity);
+
         let json = serde_json::to_string_pretty(&serde_json::json!({
             "container": container,
             "description": description,
@@ -475,6 +598,10 @@ impl RecallServer {
             "total_files": unique_paths.len(),
             "total_chunks": total_chunks,
             "has_index": true,
+            "health": indexer::maintenance::table_health(&table).await.ok(),
+            "model": indexed_model,
+            "model_mismatch": model_mismatch,
+            "last_run": last_run,
         }))
         .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
@@ -492,20 +619,19 @@ impl RecallServer {
         use futures::TryStreamExt;
         use lancedb::query::{ExecutableQuery, QueryBase};
 
-        let container =
-            container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let container = container.unwrap_or_else(|| self.state.config().active_container);
         let table_name = get_table_name(&container);
         let show_diff = show_diff.unwrap_or(true);
 
-        let seconds = parse_duration(&since).ok_or_else(|| {
-            McpError::invalid_params(format!("invalid duration '{}'. use format like '2h', '30m', '1d'", since), None)
-        })?;
-
-        let cutoff = std::time::SystemTime::now()
+        let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
-            .as_secs() as i64
-            - seconds as i64;
+            .as_secs() as i64;
+        let cutoff = indexer::query::parse_duration(&since)
+            .and_then(|seconds| indexer::query::seconds_before(now, seconds))
+            .ok_or_else(|| {
+                McpError::invalid_params(format!("invalid duration '{}'. use format like '2h', '30m', '1d'", since), None)
+            })?;
 
         let table = match self.state.db.open_table(&table_name).execute().await {
             Ok(t) => t,
@@ -518,7 +644,7 @@ impl RecallServer {
 
         let results = table
             .query()
-            .only_if(format!("mtime >= {}", cutoff))
+            .only_if(Filter::modified_between(Some(cutoff), None).to_sql())
             .select(lancedb::query::Select::Columns(vec!["path".to_string(), "mtime".to_string()]))
             .execute()
             .await
@@ -527,6 +653,7 @@ impl RecallServer {
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?;
 
+        let paths = self.state.paths(&container);
         let mut file_mtimes: std::collections::BTreeMap<String, i64> = std::collections::BTreeMap::new();
         for batch in results {
             let path_array = batch
@@ -535,9 +662,9 @@ impl RecallServer {
             let mtime_array = batch
                 .column_by_name("mtime")
                 .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
-            if let (Some(paths), Some(mtimes)) = (path_array, mtime_array) {
+            if let (Some(stored), Some(mtimes)) = (path_array, mtime_array) {
                 for i in 0..batch.num_rows() {
-                    let path = paths.value(i).to_string();
+                    let path = paths.resolve(stored.value(i));
                     let mtime = mtimes.value(i);
                     file_mtimes
                         .entry(path)
@@ -602,8 +729,7 @@ impl RecallServer {
         use futures::TryStreamExt;
         use lancedb::query::{ExecutableQuery, QueryBase};
 
-        let container =
-            container.unwrap_or_else(|| self.state.config.active_container.clone());
+        let container = container.unwrap_or_else(|| self.state.config().active_container);
         let table_name = get_table_name(&container);
         let top_k = top_k.unwrap_or(10).min(30).max(1);
 
@@ -616,10 +742,11 @@ impl RecallServer {
             }
         };
 
-        let safe_path = path.replace('\'', "''");
+        let paths = self.state.paths(&container);
+        let stored = paths.store(Path::new(&path)).unwrap_or_else(|| path.clone());
         let chunks = table
             .query()
-            .only_if(format!("path = '{}'", safe_path))
+            .only_if(Filter::path_is(&stored).to_sql())
             .execute()
             .await
             .map_err(|e| McpError::internal_error(e.to_string(), None))?
@@ -635,9 +762,7 @@ impl RecallServer {
                 use arrow_array::FixedSizeListArray;
                 if let Some(fsl) = vector_col.as_any().downcast_ref::<FixedSizeListArray>() {
                     for i in 0..batch.num_rows() {
-                        let values = fsl.value(i);
-                        if let Some(float_arr) = values.as_any().downcast_ref::<Float32Array>() {
-                            let vec: Vec<f32> = (0..float_arr.len()).map(|j| float_arr.value(j)).collect();
+                        if let Some(vec) = indexer::db::vector_values(fsl.value(i).as_ref()) {
                             match &mut avg_vector {
                                 Some(avg) => {
                                     for (k, v) in avg.iter_mut().enumerate() {
@@ -694,10 +819,10 @@ impl RecallServer {
                 .column_by_name("_distance")
                 .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
 
-            if let (Some(paths), Some(contents), Some(dists)) = (path_array, content_array, dist_array) {
+            if let (Some(stored_paths), Some(contents), Some(dists)) = (path_array, content_array, dist_array) {
                 for i in 0..batch.num_rows() {
-                    let p = paths.value(i).to_string();
-                    if p == path {
+                    let p = stored_paths.value(i).to_string();
+                    if p == stored {
                         continue;
                     }
                     let dist = dists.value(i);
@@ -723,7 +848,7 @@ impl RecallServer {
             .map(|(p, snippet, dist)| {
                 let similarity = ((1.0 - dist).clamp(0.0, 1.0) * 100.0) as u32;
                 serde_json::json!({
-                    "path": p,
+                    "path": paths.resolve(&p),
                     "snippet": snippet,
                     "similarity": similarity,
                 })
@@ -746,17 +871,17 @@ impl RecallServer {
     async fn recall_list_containers(
         &self,
     ) -> Result<CallToolResult, McpError> {
-        let containers: Vec<serde_json::Value> = self
-            .state
-            .config
+        let config = self.state.config();
+        let containers: Vec<serde_json::Value> = config
             .containers
             .iter()
             .map(|(name, info)| {
                 serde_json::json!({
                     "name": name,
                     "description": info.description,
-                    "indexed_paths": info.indexed_paths,
-                    "active": name == &self.state.config.active_container
+                    "indexed_paths": info.root_paths(),
+                    "globs": info.globs,
+                    "active": name == &config.active_container
                 })
             })
             .collect();
@@ -794,6 +919,24 @@ impl ServerHandler for RecallServer {
     }
 }
 
+/// The saved report without its sample of indexed paths.
+fn last_run_json(report: &IndexReport) -> serde_json::Value {
+    serde_json::json!({
+        "finished_at": report.finished_at,
+        "indexed": report.indexed,
+        "unchanged": report.unchanged,
+        "unsupported": report.unsupported,
+        "pruned": report.pruned,
+        "chunks_embedded": report.chunks.embedded,
+        "chunks_reused": report.chunks.reused,
+        "skipped": report.skipped,
+        "skipped_unlisted": report.skipped_unlisted,
+        "failed": report.failed,
+        "failed_unlisted": report.failed_unlisted,
+        "timings": report.timings,
+    })
+}
+
 fn get_app_data_dir() -> std::path::PathBuf {
     let base = std::env::var("APPDATA")
         .or_else(|_| std::env::var("XDG_DATA_HOME"))
@@ -818,13 +961,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let config = load_config(&config_path);
 
     let model_enum = get_embedding_model(&config.embedding_model);
+    let identity = indexer::embedding::ModelIdentity::new(&config.embedding_model);
     let model = indexer::load_model(model_enum, models_path.clone())?;
-    let reranker = indexer::load_reranker(models_path).ok();
+    let reranker = indexer::load_reranker(models_path.clone()).ok();
 
     let state = Arc::new(AppState {
         db,
-        models: Arc::new(Mutex::new(Models { model, reranker })),
-        config,
+        models: Arc::new(Mutex::new(Models { model, reranker, legacy: None })),
+        identity,
+        models_path,
+        config_path,
     });
 
     let server = RecallServer::new(state);
@@ -1,10 +1,16 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use tokio::sync::Mutex;
 
-use crate::config::{get_table_name, ConfigState};
+use crate::config::{get_table_name, Config, ConfigState, IndexedRoot, IndexingConfig, VectorConfig};
 use crate::events::{AppEvent, EventSender};
 use crate::indexer;
+use crate::indexer::doctor::Issue;
+use crate::indexer::paths::PathMap;
+use crate::indexer::report::{reports_dir, IndexReport};
+use crate::jobs::{JobControl, JobId, JobInfo, JobKind, JobManager};
 use crate::state::{
     ContainerListItem, DbState, ModelState, RerankerState, SearchResult,
 };
@@ -20,12 +26,32 @@ pub async fn get_containers(
         .map(|(name, info)| ContainerListItem {
             name: name.clone(),
             description: info.description.clone(),
-            indexed_paths: info.indexed_paths.clone(),
+            indexed_paths: info.root_paths(),
+            searched: config.searched_containers.contains(name),
         })
         .collect();
     Ok((list, config.active_container.clone()))
 }
 
+/// Adds `name` to the containers searched along with the active one, or takes it out.
+pub async fn set_container_searched(
+    name: String,
+    searched: bool,
+    config_state: &ConfigState,
+) -> Result<(), String> {
+    {
+        let mut config = config_state.config.lock().await;
+        if !config.containers.contains_key(&name) {
+            return Err("Container does not exist".to_string());
+        }
+        config.searched_containers.retain(|c| c != &name);
+        if searched {
+            config.searched_containers.push(name);
+        }
+    }
+    config_state.save().await
+}
+
 pub async fn create_container(
     name: String,
     description: String,
@@ -40,6 +66,9 @@ pub async fn create_container(
         crate::config::ContainerInfo {
             description,
             indexed_paths: Vec::new(),
+            globs: Vec::new(),
+            vectors: Default::default(),
+            fusion: Default::default(),
         },
     );
     drop(config);
@@ -61,6 +90,7 @@ pub async fn delete_container(
             config.active_container = "Default".to_string();
         }
         config.containers.remove(&name);
+        config.searched_containers.retain(|c| c != &name);
     }
 
     config_state.save().await?;
@@ -71,6 +101,9 @@ pub async fn delete_container(
     };
     let table_name = get_table_name(&name);
     let _ = db.drop_table(&table_name, &[]).await;
+    if let Err(e) = indexer::schema::clear_meta(&db, &table_name).await {
+        eprintln!("delete container {}: {}", name, e);
+    }
 
     Ok(())
 }
@@ -100,28 +133,47 @@ pub async fn set_active_container(
     Ok(())
 }
 
+/// Searches the active container and the other searched ones, see
+/// [`Config::search_scope`]. `query` may carry filters typed inline, see
+/// [`indexer::parse_query`]. Also returns the searched containers left out because
+/// they were indexed with another model than the active one.
 pub async fn search(
     query: String,
     db_state: &Arc<Mutex<DbState>>,
     model_state: &Arc<Mutex<ModelState>>,
     reranker_state: &Arc<Mutex<RerankerState>>,
     config_state: &ConfigState,
-) -> Result<Vec<SearchResult>, String> {
-    let table_name = {
-        let config = config_state.config.lock().await;
-        get_table_name(&config.active_container)
-    };
+) -> Result<(Vec<SearchResult>, Vec<String>), String> {
+    let parsed = indexer::parse_query(&query);
+    if parsed.text.is_empty() {
+        return Ok((Vec::new(), Vec::new()));
+    }
 
-    let query_vector = {
-        let mut guard = model_state.lock().await;
-        if let Some(err) = &guard.init_error {
-            return Err(format!("Model failed to load: {}", err));
+    // Fused as the active container says
+    let (mut targets, paths, fusion) = {
+        let config = config_state.config.lock().await;
+        let mut targets = Vec::new();
+        let mut paths = HashMap::new();
+        for name in config.search_scope() {
+            let info = config.containers.get(&name);
+            let map = PathMap::new(info.map(|i| i.indexed_paths.clone()).unwrap_or_default());
+            let mut filters = parsed.filters.clone();
+            filters.path_prefixes =
+                filters.path_prefixes.iter().flat_map(|p| map.stored_prefixes(p)).collect();
+            targets.push(indexer::SearchTarget {
+                table: get_table_name(&name),
+                filters,
+                vectors: info.map(|i| i.vectors.clone()).unwrap_or_default(),
+                container: name.clone(),
+            });
+            paths.insert(name, map);
         }
-        let model = guard
-            .model
-            .as_mut()
-            .ok_or("AI model is loading... Please wait a moment.")?;
-        indexer::embed_query(model, &query).map_err(|e| e.to_string())?
+        let fusion = config
+            .containers
+            .get(&config.active_container)
+            .map(|info| info.fusion)
+            .unwrap_or_default();
+        (targets, paths, fusion)
     };
 
     let db = {
@@ -129,44 +181,57 @@ pub async fn search(
         guard.db.clone()
     };
 
-    let query_variants = indexer::expand_query(&query);
+    // Other containers are only searched once they have an index
+    let tables = db.table_names().execute().await.map_err(|e| e.to_string())?;
+    let active = targets[0].container.clone();
+    targets.retain(|t| t.container == active || tables.contains(&t.table));
 
-    let vector_fut =
-        indexer::search_files(&db, &table_name, &query_vector, 50, None, None, false);
+    // One query vector serves every table: those on another model than the active one sit out
+    let identity = model_state.lock().await.identity.clone();
+    let mut indexed = None;
+    let mut searched = Vec::with_capacity(targets.len());
+    let mut skipped = Vec::new();
+    for (i, target) in targets.into_iter().enumerate() {
+        let mismatch = indexer::schema::model_mismatch(&db, &target.table, &identity)
+            .await
+            .map_err(|e| e.to_string())?;
+        if i == 0 {
+            indexed = mismatch;
+        } else if mismatch != indexed {
+            skipped.push(target.container);
+            continue;
+        }
+        searched.push(target);
+    }
+    let targets = searched;
 
-    let fts_db = db.clone();
-    let fts_table_name = table_name.clone();
-    let fts_fut = async move {
-        let futs: Vec<_> = query_variants
-            .iter()
-            .map(|variant| indexer::search_fts(&fts_db, &fts_table_name, variant, 30, None, None, false))
-            .collect();
-        let results = futures::future::join_all(futs).await;
-        let mut all_fts: Vec<(String, String)> = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        for result in results.into_iter().flatten() {
-            for item in result {
-                if seen.insert(item.0.clone()) {
-                    all_fts.push(item);
-                }
+    let query_vector = {
+        let mut guard = model_state.lock().await;
+        if let Some(err) = &guard.init_error {
+            return Err(format!("Model failed to load: {}", err));
+        }
+        match indexed {
+            // Being re-embedded: until the swap, the live table answers with its old model
+            Some(indexed) => match guard.legacy.as_mut().filter(|l| l.identity == indexed) {
+                Some(legacy) => legacy.embed_query(&parsed.text).map_err(|e| e.to_string())?,
+                None => return Err(indexer::schema::mismatch_error(&indexed, &identity).to_string()),
+            },
+            None => {
+                let model = guard
+                    .model
+                    .as_mut()
+                    .ok_or("AI model is loading... Please wait a moment.")?;
+                indexer::embed_query(model, &parsed.text).map_err(|e| e.to_string())?
             }
         }
-        all_fts
-    };
-
-    let (vector_result, fts_results) = tokio::join!(vector_fut, fts_fut);
-
-    let vector_results = vector_result.map_err(|e| e.to_string())?;
-
-    let merged = if fts_results.is_empty() {
-        vector_results
-    } else {
-        indexer::hybrid_merge(&vector_results, &fts_results, 50)
     };
 
-    let rerank_input: Vec<(String, String, f32)> = merged.into_iter().take(15).collect();
+    let (merged, used_hybrid) =
+        indexer::search_pipeline(&db, &targets, &parsed.text, &query_vector, 50, &fusion, true)
+            .await
+            .map_err(|e| e.to_string())?;
 
-    let used_hybrid = !fts_results.is_empty();
+    let rerank_input: Vec<indexer::SearchHit> = merged.into_iter().take(15).collect();
 
     let (final_results, used_reranker) = {
         let reranker_opt = {
@@ -175,7 +240,7 @@ pub async fn search(
         };
         if let Some(reranker) = reranker_opt {
             let (reranker_back, results, used) =
-                indexer::safe_rerank(reranker, query.clone(), rerank_input).await;
+                indexer::safe_rerank(reranker, parsed.text.clone(), rerank_input).await;
             {
                 let mut guard = reranker_state.lock().await;
                 if let Some(r) = reranker_back {
@@ -190,10 +255,16 @@ pub async fn search(
 
     let scored: Vec<SearchResult> = crate::indexer::pipeline::score_results(final_results, used_reranker, used_hybrid, 20)
         .into_iter()
-        .map(|r| SearchResult { path: r.path, snippet: r.snippet, score: r.score })
+        .map(|r| SearchResult {
+            path: paths.get(&r.container).map_or_else(|| r.path.clone(), |p| p.resolve(&r.path)),
+            container: r.container,
+            snippet: r.snippet,
+            score: r.score,
+            meta: r.meta,
+        })
         .collect();
 
-    Ok(scored)
+    Ok((scored, skipped))
 }
 
 pub async fn index_folder(
@@ -202,24 +273,42 @@ pub async fn index_folder(
     model_state: &Arc<Mutex<ModelState>>,
     config_state: &ConfigState,
     watcher_state: &watcher::WatcherState,
+    jobs: &JobManager,
     tx: EventSender,
 ) -> Result<String, String> {
-    let table_name = {
+    let (container, table_name) = {
         let config = config_state.config.lock().await;
-        get_table_name(&config.active_container)
+        (
+            config.active_container.clone(),
+            get_table_name(&config.active_container),
+        )
     };
 
-    {
+    // Started before the folder is added, so a busy container is left as it was
+    let (job_id, job) = jobs
+        .start(JobKind::IndexFolder, &container, std::slice::from_ref(&dir))
+        .await?;
+
+    let added = async {
         let mut config = config_state.config.lock().await;
-        let active = config.active_container.clone();
-        if let Some(info) = config.containers.get_mut(&active) {
-            if !info.indexed_paths.contains(&dir) {
-                info.indexed_paths.push(dir.clone());
-            }
-        }
+        let info = config
+            .containers
+            .get_mut(&container)
+            .ok_or("Active container not found")?;
+        let root = info.add_root(&dir);
+        let paths = PathMap::new(info.indexed_paths.clone());
         drop(config);
         config_state.save().await?;
+        Ok::<_, String>((root, paths))
     }
+    .await;
+    let (root, paths) = match added {
+        Ok(added) => added,
+        Err(e) => {
+            jobs.finish(job_id, &Err::<(), _>(anyhow::anyhow!(e.clone()))).await;
+            return Err(e);
+        }
+    };
 
     let db = {
         let guard = db_state.lock().await;
@@ -229,17 +318,26 @@ pub async fn index_folder(
     let ms = model_state.clone();
     let progress_tx = tx.clone();
 
-    let indexing_config = {
+    let (indexing_config, globs, vectors) = {
         let config = config_state.config.lock().await;
-        config.indexing.clone()
+        let (globs, vectors) = config
+            .containers
+            .get(&container)
+            .map(|info| (info.globs.clone(), info.vectors.clone()))
+            .unwrap_or_default();
+        (config.indexing.clone(), globs, vectors)
     };
 
-    let count = indexer::index_directory(
-        &dir,
+    let result = indexer::index_directory(
+        &root,
+        &paths,
         &table_name,
         &db,
         &ms,
         &indexing_config,
+        &globs,
+        &vectors,
+        &job,
         move |current, total, path| {
             let _ = progress_tx.send(AppEvent::IndexingProgress {
                 current,
@@ -248,13 +346,10 @@ pub async fn index_folder(
             });
         },
     )
-    .await
-    .map_err(|e| e.to_string())?;
-
-    let _ = tx.send(AppEvent::IndexingComplete(format!(
-        "{} files indexed",
-        count
-    )));
+    .await;
+    jobs.finish(job_id, &result).await;
+    let report = result.map_err(|e| rebuild_or_message(&container, e, &tx))?;
+    publish_report(config_state, &container, &report, &tx);
 
     let db2 = {
         let guard = db_state.lock().await;
@@ -262,7 +357,7 @@ pub async fn index_folder(
     };
     watcher::restart(watcher_state, config_state, db2, model_state.clone(), tx).await;
 
-    Ok(format!("Indexed {} files", count))
+    Ok(format!("Indexed {} files", report.indexed))
 }
 
 pub async fn reset_index(
@@ -287,21 +382,241 @@ pub async fn reindex_all(
     db_state: &Arc<Mutex<DbState>>,
     model_state: &Arc<Mutex<ModelState>>,
     config_state: &ConfigState,
+    jobs: &JobManager,
+    tx: EventSender,
+) -> Result<String, String> {
+    let container = {
+        let config = config_state.config.lock().await;
+        config.active_container.clone()
+    };
+    reindex_container(&container, db_state, model_state, config_state, jobs, tx).await
+}
+
+/// Fixes a table that can't be migrated in place, once the user confirmed it.
+/// A table built with another known model is re-embedded while it keeps serving
+/// queries; anything else is dropped and indexed again from scratch.
+pub async fn rebuild_container(
+    container: String,
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+    jobs: &JobManager,
     tx: EventSender,
 ) -> Result<String, String> {
-    let (table_name, paths) = {
+    let (db, path) = {
+        let guard = db_state.lock().await;
+        (guard.db.clone(), guard.path.clone())
+    };
+    let table_name = get_table_name(&container);
+    let identity = model_state.lock().await.identity.clone();
+    let indexed = indexer::schema::model_mismatch(&db, &table_name, &identity)
+        .await
+        .map_err(|e| e.to_string())?;
+    if indexed.is_some() {
+        return reembed_container(&container, db_state, model_state, config_state, jobs, tx).await;
+    }
+
+    indexer::reset_index(&path, &table_name)
+        .await
+        .map_err(|e| e.to_string())?;
+    reindex_container(&container, db_state, model_state, config_state, jobs, tx).await
+}
+
+/// Settles interrupted re-embeds, migrates older tables and rewrites their rows, which hold
+/// absolute paths, relative to their container's roots. Nothing is re-embedded; a container without a table is skipped.
+pub async fn rebase_paths(
+    db_state: &Arc<Mutex<DbState>>,
+    config_state: &ConfigState,
+) -> Result<(), String> {
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let containers: Vec<(String, PathMap)> = {
+        let config = config_state.config.lock().await;
+        config
+            .containers
+            .iter()
+            .map(|(name, info)| (name.clone(), PathMap::new(info.indexed_paths.clone())))
+            .collect()
+    };
+
+    for (container, paths) in containers {
+        let table_name = get_table_name(&container);
+        let Ok(table) = db.open_table(&table_name).execute().await else {
+            continue;
+        };
+        // A re-embed the app did not survive may have swapped the rows without recording their model
+        if let Err(e) = indexer::schema::settle_swap(&db, &table_name).await {
+            eprintln!("rebase paths {}: {}", container, e);
+            continue;
+        }
+        // The rewrite keys rows by chunk_index, which the oldest tables only get here
+        if let Err(e) = indexer::schema::migrate(&db, &table, &table_name).await {
+            eprintln!("rebase paths {}: {}", container, e);
+            continue;
+        }
+        if let Err(e) = indexer::paths::rebase(&table, &paths, &paths).await {
+            eprintln!("rebase paths {}: {}", container, e);
+        }
+    }
+    Ok(())
+}
+
+/// Moves every container built with another embedding model to the configured one,
+/// one after the other. Run once the model is loaded.
+pub async fn migrate_models(
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+    jobs: &JobManager,
+    tx: EventSender,
+) -> Result<(), String> {
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let containers: Vec<String> = {
+        let config = config_state.config.lock().await;
+        config.containers.keys().cloned().collect()
+    };
+    let identity = model_state.lock().await.identity.clone();
+
+    for container in containers {
+        let stale = indexer::schema::model_mismatch(&db, &get_table_name(&container), &identity)
+            .await
+            .map_err(|e| e.to_string())?;
+        if stale.is_none() {
+            continue;
+        }
+        if let Err(e) =
+            reembed_container(&container, db_state, model_state, config_state, jobs, tx.clone()).await
+        {
+            let _ = tx.send(AppEvent::IndexingComplete(e));
+        }
+    }
+    Ok(())
+}
+
+/// Blue/green switch of one container to the configured model: re-embeds into a shadow
+/// table, swaps it in, then catches up on files that changed in the meantime.
+async fn reembed_container(
+    container: &str,
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+    jobs: &JobManager,
+    tx: EventSender,
+) -> Result<String, String> {
+    let (table_name, roots, globs, vectors, indexing_config) = {
         let config = config_state.config.lock().await;
         let info = config
             .containers
-            .get(&config.active_container)
-            .ok_or("Active container not found")?;
+            .get(container)
+            .ok_or("Container not found")?;
         (
-            get_table_name(&config.active_container),
+            get_table_name(container),
             info.indexed_paths.clone(),
+            info.globs.clone(),
+            info.vectors.clone(),
+            config.indexing.clone(),
         )
     };
+    let paths = PathMap::new(roots.clone());
 
-    if paths.is_empty() {
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+
+    let (identity, cache_dir) = {
+        let guard = model_state.lock().await;
+        (guard.identity.clone(), guard.cache_dir.clone())
+    };
+    let Some(indexed) = indexer::schema::model_mismatch(&db, &table_name, &identity)
+        .await
+        .map_err(|e| e.to_string())?
+    else {
+        return Ok(format!("{} already uses {}", container, identity.name));
+    };
+
+    let root_paths: Vec<String> = roots.iter().map(|r| r.path.clone()).collect();
+    let (job_id, job) = jobs.start(JobKind::ReembedModel, container, &root_paths).await?;
+
+    // Without the old model the table can't be searched meanwhile, but the migration still runs
+    match tokio::task::spawn_blocking(move || indexer::embedding::LegacyModel::load(indexed, cache_dir)).await {
+        Ok(Ok(legacy)) => model_state.lock().await.legacy = Some(legacy),
+        Ok(Err(e)) => eprintln!("reembed {}: previous model: {}", container, e),
+        Err(e) => eprintln!("reembed {}: previous model: {}", container, e),
+    }
+    let progress_tx = tx.clone();
+    let progress_container = container.to_string();
+    let result = async {
+        let chunks = indexer::reembed::reembed_table(
+            &db,
+            &table_name,
+            model_state,
+            &vectors,
+            &job,
+            move |current, total| {
+                let _ = progress_tx.send(AppEvent::ReembedProgress {
+                    container: progress_container.clone(),
+                    current,
+                    total,
+                });
+            },
+        )
+        .await?;
+        let report = index_roots(
+            &roots,
+            &paths,
+            &table_name,
+            &db,
+            model_state,
+            &indexing_config,
+            &globs,
+            &vectors,
+            &job,
+            &tx,
+        )
+        .await?;
+        Ok::<_, anyhow::Error>((chunks, report))
+    }
+    .await;
+    jobs.finish(job_id, &result).await;
+    model_state.lock().await.legacy = None;
+
+    let (chunks, report) = result.map_err(|e| e.to_string())?;
+    publish_report(config_state, container, &report, &tx);
+    Ok(format!(
+        "Re-embedded {} chunks of {} with {}",
+        chunks, container, identity.name
+    ))
+}
+
+async fn reindex_container(
+    container: &str,
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+    jobs: &JobManager,
+    tx: EventSender,
+) -> Result<String, String> {
+    let (table_name, roots, globs, vectors) = {
+        let config = config_state.config.lock().await;
+        let info = config
+            .containers
+            .get(container)
+            .ok_or("Container not found")?;
+        (
+            get_table_name(container),
+            info.indexed_paths.clone(),
+            info.globs.clone(),
+            info.vectors.clone(),
+        )
+    };
+
+    if roots.is_empty() {
         return Err("No folders to reindex".to_string());
     }
 
@@ -317,15 +632,168 @@ pub async fn reindex_all(
         config.indexing.clone()
     };
 
-    let mut total = 0;
-    for dir in &paths {
-        let progress_tx = tx.clone();
-        let count = indexer::index_directory(
-            dir,
+    let paths = PathMap::new(roots.clone());
+    let root_paths: Vec<String> = roots.iter().map(|r| r.path.clone()).collect();
+    let (job_id, job) = jobs.start(JobKind::ReindexAll, container, &root_paths).await?;
+    let result = index_roots(
+        &roots,
+        &paths,
+        &table_name,
+        &db,
+        &ms,
+        &indexing_config,
+        &globs,
+        &vectors,
+        &job,
+        &tx,
+    )
+    .await;
+    jobs.finish(job_id, &result).await;
+    let report = result.map_err(|e| rebuild_or_message(container, e, &tx))?;
+    publish_report(config_state, container, &report, &tx);
+
+    Ok(format!(
+        "Reindexed {} files from {} folders",
+        report.indexed,
+        roots.len()
+    ))
+}
+
+/// Picks up every job a previous session left unfinished. Roots that were removed
+/// from their container since then are skipped, as are containers that no longer exist.
+pub async fn resume_interrupted_jobs(
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+    jobs: &JobManager,
+    tx: EventSender,
+) -> Result<(), String> {
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+
+    // Paused jobs wait for the user, so they go last
+    let mut checkpoints = jobs.interrupted();
+    checkpoints.sort_by_key(|c| c.paused);
+    for mut checkpoint in checkpoints {
+        let (table_name, indexing_config, globs, vectors, roots, paths) = {
+            let config = config_state.config.lock().await;
+            let Some(info) = config.containers.get(&checkpoint.container) else {
+                jobs.discard(&checkpoint.container);
+                continue;
+            };
+            checkpoint.roots.retain(|r| info.root(r).is_some());
+            let roots: Vec<IndexedRoot> = checkpoint
+                .roots
+                .iter()
+                .filter_map(|r| info.root(r).cloned())
+                .collect();
+            (
+                get_table_name(&checkpoint.container),
+                config.indexing.clone(),
+                info.globs.clone(),
+                info.vectors.clone(),
+                roots,
+                PathMap::new(info.indexed_paths.clone()),
+            )
+        };
+        // A re-embed starts over from the live table, `migrate_models` runs it again
+        if checkpoint.roots.is_empty() || checkpoint.kind == JobKind::ReembedModel {
+            jobs.discard(&checkpoint.container);
+            continue;
+        }
+
+        let container = checkpoint.container.clone();
+        let (job_id, job) = match jobs.resume_checkpoint(checkpoint).await {
+            Ok(started) => started,
+            Err(e) => {
+                let _ = tx.send(AppEvent::IndexingComplete(e));
+                continue;
+            }
+        };
+        let result = index_roots(
+            &roots,
+            &paths,
             &table_name,
             &db,
-            &ms,
+            model_state,
             &indexing_config,
+            &globs,
+            &vectors,
+            &job,
+            &tx,
+        )
+        .await;
+        jobs.finish(job_id, &result).await;
+
+        match result {
+            Ok(report) => publish_report(config_state, &container, &report, &tx),
+            Err(e) => {
+                let message = rebuild_or_message(&container, e, &tx);
+                let _ = tx.send(AppEvent::IndexingComplete(message));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Stores the report for MCP and hands it to the GUI, followed by the usual completion message.
+fn publish_report(
+    config_state: &ConfigState,
+    container: &str,
+    report: &IndexReport,
+    tx: &EventSender,
+) {
+    if let Err(e) = report.save(&reports_dir(&config_state.path), container) {
+        eprintln!("index report {}: {}", container, e);
+    }
+    let _ = tx.send(AppEvent::IndexingReport {
+        container: container.to_string(),
+        report: report.clone(),
+    });
+    let _ = tx.send(AppEvent::IndexingComplete(report.summary()));
+}
+
+/// Turns an indexing error into its message, first asking the GUI for confirmation
+/// when the table has to be rebuilt.
+fn rebuild_or_message(container: &str, error: anyhow::Error, tx: &EventSender) -> String {
+    if let Some(reason) = indexer::schema::rebuild_reason(&error) {
+        let _ = tx.send(AppEvent::RebuildRequired {
+            container: container.to_string(),
+            reason,
+        });
+    }
+    error.to_string()
+}
+
+/// Indexes each root in turn under one job and sums up the reports.
+async fn index_roots(
+    roots: &[IndexedRoot],
+    paths: &PathMap,
+    table_name: &str,
+    db: &lancedb::connection::Connection,
+    model_state: &Arc<Mutex<ModelState>>,
+    indexing_config: &IndexingConfig,
+    globs: &[String],
+    vectors: &VectorConfig,
+    job: &JobControl,
+    tx: &EventSender,
+) -> anyhow::Result<IndexReport> {
+    let mut total = IndexReport::default();
+    for root in roots {
+        let progress_tx = tx.clone();
+        total += indexer::index_directory(
+            root,
+            paths,
+            table_name,
+            db,
+            model_state,
+            indexing_config,
+            globs,
+            vectors,
+            job,
             move |current, total, path| {
                 let _ = progress_tx.send(AppEvent::IndexingProgress {
                     current,
@@ -334,20 +802,278 @@ pub async fn reindex_all(
                 });
             },
         )
+        .await?;
+    }
+    Ok(total)
+}
+
+pub async fn remove_folder(
+    dir: String,
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+    watcher_state: &watcher::WatcherState,
+    jobs: &JobManager,
+    tx: EventSender,
+) -> Result<String, String> {
+    // A job would write the folder's rows back after they're deleted; none may start
+    // until the rows are gone
+    let active = config_state.config.lock().await.active_container.clone();
+    let _reservation = jobs.reserve(&active, "removing a folder").await?;
+
+    let (table_name, root, before, after) = {
+        let mut config = config_state.config.lock().await;
+        let info = config
+            .containers
+            .get_mut(&active)
+            .ok_or("Active container not found")?;
+        let root = info
+            .root(&dir)
+            .cloned()
+            .ok_or("Folder is not indexed in this container")?;
+        let before = PathMap::new(info.indexed_paths.clone());
+        info.indexed_paths.retain(|r| r.id != root.id);
+        (
+            get_table_name(&active),
+            root,
+            before,
+            PathMap::new(info.indexed_paths.clone()),
+        )
+    };
+    config_state.save().await?;
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let removed = indexer::remove_directory(&root, &before, &after, &table_name, &db)
         .await
         .map_err(|e| e.to_string())?;
-        total += count;
+
+    watcher::restart(watcher_state, config_state, db, model_state.clone(), tx).await;
+
+    Ok(format!("Removed {} files from the index", removed))
+}
+
+/// Drops rows of files that disappeared from the active container's folders
+/// while nothing was watching, without re-embedding anything.
+pub async fn prune_index(
+    db_state: &Arc<Mutex<DbState>>,
+    config_state: &ConfigState,
+) -> Result<String, String> {
+    let (table_name, roots, globs) = {
+        let config = config_state.config.lock().await;
+        let info = config
+            .containers
+            .get(&config.active_container)
+            .ok_or("Active container not found")?;
+        (
+            get_table_name(&config.active_container),
+            info.indexed_paths.clone(),
+            info.globs.clone(),
+        )
+    };
+    let paths = PathMap::new(roots.clone());
+
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+
+    let mut pruned = 0;
+    for root in &roots {
+        pruned += indexer::prune_directory(root, &paths, &globs, &table_name, &db)
+            .await
+            .map_err(|e| e.to_string())?;
     }
 
-    let _ = tx.send(AppEvent::IndexingComplete(format!(
-        "{} files reindexed from {} folders",
-        total,
-        paths.len()
-    )));
+    Ok(format!("Removed {} missing files", pruned))
+}
 
+/// Looks for orphan tables and broken containers across the whole database. Changes nothing;
+/// containers a job is writing to are skipped.
+pub async fn check_index(
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+    jobs: &JobManager,
+) -> Result<Vec<Issue>, String> {
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let containers = config_state.config.lock().await.containers.clone();
+    let dim = indexer::get_model_dim(model_state)
+        .await
+        .map_err(|e| e.to_string())?;
+    let busy: HashSet<String> = jobs.list().await.into_iter().map(|j| j.container).collect();
+    indexer::doctor::check(&db, &containers, dim, &busy)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Fixes what can be fixed in place among the `issues` the user confirmed, and nothing
+/// else. A table with the wrong vector dimension goes through the usual rebuild prompt;
+/// a container without a table is only reported.
+pub async fn repair_index(
+    issues: Vec<Issue>,
+    db_state: &Arc<Mutex<DbState>>,
+    config_state: &ConfigState,
+    jobs: &JobManager,
+    tx: EventSender,
+) -> Result<String, String> {
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let containers = config_state.config.lock().await.containers.clone();
+    let repaired = indexer::doctor::repair(&db, &containers, &issues, jobs)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for issue in &issues {
+        if let Issue::WrongDimension { container, .. } = issue {
+            let _ = tx.send(AppEvent::RebuildRequired {
+                container: container.clone(),
+                reason: issue.describe(),
+            });
+        }
+    }
+
+    let left: Vec<String> = issues
+        .iter()
+        .filter(|i| !repaired.contains(i))
+        .map(Issue::describe)
+        .collect();
+    if left.is_empty() {
+        Ok(format!("Repaired {} problems", repaired.len()))
+    } else {
+        Ok(format!(
+            "Repaired {} problems, {} left: {}",
+            repaired.len(),
+            left.len(),
+            left.join("; ")
+        ))
+    }
+}
+
+/// Writes a container's table, folders and model to a single archive another machine can import.
+pub async fn export_container(
+    container: String,
+    dest: PathBuf,
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+) -> Result<String, String> {
+    let info = {
+        let config = config_state.config.lock().await;
+        config
+            .containers
+            .get(&container)
+            .cloned()
+            .ok_or("Container not found")?
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+    let table_name = get_table_name(&container);
+    if db.open_table(&table_name).execute().await.is_err() {
+        return Err("Nothing to export: this container has no index yet".to_string());
+    }
+
+    // Brings an older table to the current schema and records its model before it leaves
+    let dim = indexer::get_model_dim(model_state)
+        .await
+        .map_err(|e| e.to_string())?;
+    let identity = model_state.lock().await.identity.clone();
+    indexer::db::get_or_create_table(&db, &table_name, dim, &identity, &info.vectors)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let manifest =
+        indexer::archive::export_table(&db, &table_name, &container, info, &identity, &dest)
+            .await
+            .map_err(|e| e.to_string())?;
     Ok(format!(
-        "Reindexed {} files from {} folders",
-        total,
-        paths.len()
+        "Exported {} chunks to {}",
+        manifest.chunks,
+        dest.display()
     ))
 }
+
+/// Adds the container stored in an archive, under a new name if its own is taken.
+/// The archive must come from the same embedding model as this install.
+pub async fn import_container(
+    src: PathBuf,
+    db_state: &Arc<Mutex<DbState>>,
+    model_state: &Arc<Mutex<ModelState>>,
+    config_state: &ConfigState,
+) -> Result<String, String> {
+    let manifest = indexer::archive::read_manifest(&src).map_err(|e| e.to_string())?;
+    let name = {
+        let config = config_state.config.lock().await;
+        unused_container_name(&config, &manifest.container)
+    };
+    let db = {
+        let guard = db_state.lock().await;
+        guard.db.clone()
+    };
+
+    let dim = indexer::get_model_dim(model_state)
+        .await
+        .map_err(|e| e.to_string())?;
+    let identity = model_state.lock().await.identity.clone();
+    let manifest =
+        indexer::archive::import_table(&db, &get_table_name(&name), &src, &identity, dim)
+            .await
+            .map_err(|e| e.to_string())?;
+
+    {
+        let mut config = config_state.config.lock().await;
+        config.containers.insert(name.clone(), manifest.info.clone());
+    }
+    config_state.save().await?;
+
+    // Roots keep the exporting machine's paths; rows are stored relative to them
+    let missing: Vec<&str> = manifest
+        .info
+        .indexed_paths
+        .iter()
+        .filter(|root| !Path::new(&root.path).is_dir())
+        .map(|root| root.path.as_str())
+        .collect();
+    if missing.is_empty() {
+        return Ok(format!("Imported {} chunks into '{}'", manifest.chunks, name));
+    }
+    Ok(format!(
+        "Imported {} chunks into '{}'. Not found on this machine: {}. Point their \"path\" in {} at the local copies, the index stays valid.",
+        manifest.chunks,
+        name,
+        missing.join(", "),
+        config_state.path.display()
+    ))
+}
+
+fn unused_container_name(config: &Config, wanted: &str) -> String {
+    std::iter::once(wanted.to_string())
+        .chain((2..).map(|n| format!("{} {}", wanted, n)))
+        .find(|name| !config.containers.contains_key(name))
+        .unwrap_or_else(|| wanted.to_string())
+}
+
+pub async fn list_jobs(jobs: &JobManager) -> Result<Vec<JobInfo>, String> {
+    Ok(jobs.list().await)
+}
+
+pub async fn pause_job(id: JobId, jobs: &JobManager) -> Result<(), String> {
+    jobs.pause(id).await
+}
+
+pub async fn resume_job(id: JobId, jobs: &JobManager) -> Result<(), String> {
+    jobs.resume(id).await
+}
+
+pub async fn cancel_job(id: JobId, jobs: &JobManager) -> Result<(), String> {
+    jobs.cancel(id).await
+}
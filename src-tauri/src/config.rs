@@ -15,6 +15,10 @@ pub struct IndexingConfig {
     pub chunk_overlap: Option<usize>,
     #[serde(default = "default_true")]
     pub use_git_history: bool,
+    /// Take files whose mtime and size match the index as unchanged without hashing them.
+    /// Faster walks, but an edit that keeps both is missed.
+    #[serde(default)]
+    pub quick_check: bool,
 }
 
 impl Default for IndexingConfig {
@@ -25,14 +29,211 @@ impl Default for IndexingConfig {
             chunk_size: None,
             chunk_overlap: None,
             use_git_history: true,
+            quick_check: false,
         }
     }
 }
 
+/// Thresholds of the background maintenance task (compaction, index refresh).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct MaintenanceConfig {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default = "default_maintenance_interval")]
+    pub interval_minutes: u64,
+    #[serde(default = "default_max_small_fragments")]
+    pub max_small_fragments: usize,
+    #[serde(default = "default_max_unindexed_rows")]
+    pub max_unindexed_rows: usize,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_minutes: default_maintenance_interval(),
+            max_small_fragments: default_max_small_fragments(),
+            max_unindexed_rows: default_max_unindexed_rows(),
+        }
+    }
+}
+
+/// How vectors are stored on disk. `Float16` halves the size for a negligible recall loss.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum VectorPrecision {
+    #[default]
+    Float32,
+    Float16,
+}
+
+/// ANN index built over a container's vectors. `Auto` leaves the choice to LanceDB.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum AnnIndexType {
+    #[default]
+    Auto,
+    IvfFlat,
+    IvfPq,
+    IvfHnswPq,
+    IvfHnswSq,
+}
+
+/// Per-container storage and search settings of the vector column.
+#[derive(Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct VectorConfig {
+    /// Changing it on an indexed container asks for a rebuild.
+    #[serde(default)]
+    pub precision: VectorPrecision,
+    /// Changing it rebuilds the index at the next refresh, nothing is re-embedded.
+    #[serde(default)]
+    pub index: AnnIndexType,
+    /// IVF partitions probed per query. More is slower and finds more.
+    pub nprobes: Option<usize>,
+    /// Re-ranks `limit * refine_factor` candidates on full vectors, for quantized indexes.
+    pub refine_factor: Option<u32>,
+}
+
+/// How the vector and BM25 result lists of a hybrid search are combined.
+#[derive(Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionStrategy {
+    /// Reciprocal rank fusion: only ranks count, scores of either list are ignored.
+    #[default]
+    Rrf,
+    /// Weighted sum of both scores, each min-max normalized over its list.
+    Convex,
+    VectorOnly,
+    FtsOnly,
+}
+
+impl FusionStrategy {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rrf" => Some(Self::Rrf),
+            "convex" => Some(Self::Convex),
+            "vector_only" => Some(Self::VectorOnly),
+            "fts_only" => Some(Self::FtsOnly),
+            _ => None,
+        }
+    }
+}
+
+/// Per-container fusion of hybrid search results. Weights are relative: `1.0` and `2.0`
+/// make BM25 count twice as much as vectors.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct FusionConfig {
+    #[serde(default)]
+    pub strategy: FusionStrategy,
+    /// RRF smoothing constant. Lower values favor the top of each list.
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f32,
+    #[serde(default = "default_weight")]
+    pub vector_weight: f32,
+    #[serde(default = "default_weight")]
+    pub fts_weight: f32,
+}
+
+impl Default for FusionConfig {
+    fn default() -> Self {
+        Self {
+            strategy: FusionStrategy::default(),
+            rrf_k: default_rrf_k(),
+            vector_weight: default_weight(),
+            fts_weight: default_weight(),
+        }
+    }
+}
+
+/// A folder indexed in a container. Rows store their path relative to it, under `id`,
+/// so moving the folder on disk only takes a new `path` here.
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct IndexedRoot {
+    pub id: String,
+    pub path: String,
+}
+
+impl IndexedRoot {
+    fn new(id: String, path: &str) -> Self {
+        Self {
+            id,
+            path: path.to_string(),
+        }
+    }
+}
+
+/// Short, stable id derived from the path the root was first added with. `salt` is only
+/// non-zero when that id is already taken, e.g. by a root that moved away from this path.
+fn root_id(path: &str, salt: u32) -> String {
+    let hash = blake3::hash(format!("{}\0{}", path, salt).as_bytes()).to_hex();
+    hash.as_str()[..8].to_string()
+}
+
+/// Accepts both roots and the plain path strings of older configs.
+fn deserialize_roots<'de, D>(deserializer: D) -> Result<Vec<IndexedRoot>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Entry {
+        Root(IndexedRoot),
+        Path(String),
+    }
+    let mut roots: Vec<IndexedRoot> = Vec::new();
+    for entry in Vec::<Entry>::deserialize(deserializer)? {
+        match entry {
+            Entry::Root(root) => roots.push(root),
+            Entry::Path(path) => {
+                let id = unused_root_id(&roots, &path);
+                roots.push(IndexedRoot::new(id, &path));
+            }
+        }
+    }
+    Ok(roots)
+}
+
+fn unused_root_id(roots: &[IndexedRoot], path: &str) -> String {
+    (0..)
+        .map(|salt| root_id(path, salt))
+        .find(|id| !roots.iter().any(|r| &r.id == id))
+        .unwrap_or_default()
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct ContainerInfo {
     pub description: String,
-    pub indexed_paths: Vec<String>,
+    #[serde(deserialize_with = "deserialize_roots")]
+    pub indexed_paths: Vec<IndexedRoot>,
+    /// Include/exclude globs applied under every indexed path, `!` to exclude.
+    #[serde(default)]
+    pub globs: Vec<String>,
+    /// Storage precision, ANN index and query tuning of this container's vectors.
+    #[serde(default)]
+    pub vectors: VectorConfig,
+    /// How vector and BM25 hits are combined when searching this container.
+    #[serde(default)]
+    pub fusion: FusionConfig,
+}
+
+impl ContainerInfo {
+    pub fn root_paths(&self) -> Vec<String> {
+        self.indexed_paths.iter().map(|r| r.path.clone()).collect()
+    }
+
+    pub fn root(&self, path: &str) -> Option<&IndexedRoot> {
+        self.indexed_paths.iter().find(|r| r.path == path)
+    }
+
+    /// The root at `path`, added with a fresh id if the container doesn't index it yet.
+    pub fn add_root(&mut self, path: &str) -> IndexedRoot {
+        if let Some(root) = self.root(path) {
+            return root.clone();
+        }
+        let root = IndexedRoot::new(unused_root_id(&self.indexed_paths, path), path);
+        self.indexed_paths.push(root.clone());
+        root
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -42,6 +243,8 @@ pub struct Config {
     pub embedding_model: String,
     #[serde(default)]
     pub indexing: IndexingConfig,
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
     #[serde(default = "default_hotkey")]
     pub hotkey: String,
     #[serde(default = "default_true")]
@@ -52,6 +255,22 @@ pub struct Config {
     pub locale: String,
     pub containers: HashMap<String, ContainerInfo>,
     pub active_container: String,
+    /// Containers the search bar queries along with the active one.
+    #[serde(default)]
+    pub searched_containers: Vec<String>,
+}
+
+impl Config {
+    /// The active container first, then the other searched ones that still exist.
+    pub fn search_scope(&self) -> Vec<String> {
+        let mut scope = vec![self.active_container.clone()];
+        for name in &self.searched_containers {
+            if self.containers.contains_key(name) && !scope.contains(name) {
+                scope.push(name.clone());
+            }
+        }
+        scope
+    }
 }
 
 fn default_schema() -> String {
@@ -71,6 +290,26 @@ fn default_locale() -> String {
     "auto".to_string()
 }
 
+fn default_rrf_k() -> f32 {
+    60.0
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+fn default_maintenance_interval() -> u64 {
+    30
+}
+
+fn default_max_small_fragments() -> usize {
+    32
+}
+
+fn default_max_unindexed_rows() -> usize {
+    1000
+}
+
 impl Default for Config {
     fn default() -> Self {
         let mut containers = HashMap::new();
@@ -79,18 +318,23 @@ impl Default for Config {
             ContainerInfo {
                 description: String::new(),
                 indexed_paths: Vec::new(),
+                globs: Vec::new(),
+                vectors: VectorConfig::default(),
+                fusion: FusionConfig::default(),
             },
         );
         Self {
             schema: default_schema(),
             embedding_model: "MultilingualE5Base".to_string(),
             indexing: IndexingConfig::default(),
+            maintenance: MaintenanceConfig::default(),
             hotkey: default_hotkey(),
             always_on_top: true,
             launch_at_startup: false,
             locale: default_locale(),
             containers,
             active_container: "Default".to_string(),
+            searched_containers: Vec::new(),
         }
     }
 }
@@ -267,6 +511,9 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                             ContainerInfo {
                                 description: String::new(),
                                 indexed_paths: Vec::new(),
+                                globs: Vec::new(),
+                                vectors: VectorConfig::default(),
+                                fusion: FusionConfig::default(),
                             },
                         );
                     }
@@ -277,6 +524,9 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                         ContainerInfo {
                             description: String::new(),
                             indexed_paths: Vec::new(),
+                            globs: Vec::new(),
+                            vectors: VectorConfig::default(),
+                            fusion: FusionConfig::default(),
                         },
                     );
                 }
@@ -291,12 +541,14 @@ pub fn load_config(config_path: &std::path::Path) -> Config {
                         .embedding_model
                         .unwrap_or_else(|| "MultilingualE5Base".to_string()),
                     indexing: IndexingConfig::default(),
+                    maintenance: MaintenanceConfig::default(),
                     hotkey: default_hotkey(),
                     always_on_top: true,
                     launch_at_startup: false,
                     locale: default_locale(),
                     active_container: old.active_container.unwrap_or(default_active),
                     containers,
+                    searched_containers: Vec::new(),
                 }
             } else {
                 Config::default()
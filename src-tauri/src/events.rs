@@ -1,5 +1,8 @@
 use serde::Serialize;
 
+use crate::indexer::report::IndexReport;
+use crate::jobs::{JobId, JobInfo, JobState};
+
 #[derive(Debug, Clone, Serialize)]
 pub enum AppEvent {
     IndexingProgress {
@@ -8,10 +11,34 @@ pub enum AppEvent {
         path: String,
     },
     IndexingComplete(String),
+    IndexingReport {
+        container: String,
+        report: IndexReport,
+    },
+    /// Chunks re-embedded so far while a container moves to a new model.
+    ReembedProgress {
+        container: String,
+        current: usize,
+        total: usize,
+    },
+    /// The container's table can only be fixed by dropping and reindexing it. Asks the user first.
+    RebuildRequired {
+        container: String,
+        reason: String,
+    },
     ModelLoaded,
     ModelLoadError(String),
     RerankerLoaded,
     RerankerLoadError(String),
+    JobStarted(JobInfo),
+    JobStateChanged {
+        id: JobId,
+        state: JobState,
+    },
+    JobFinished {
+        id: JobId,
+        state: JobState,
+    },
 }
 
 pub type EventSender = std::sync::mpsc::Sender<AppEvent>;
@@ -69,6 +69,8 @@ fn en(key: &str) -> &'static str {
     match key {
         "search_placeholder" => "Search in {{container}}...",
         "index_folder_title" => "Index Folder into {{container}} (Ctrl+O)",
+        "export_title" => "Export {{container}}",
+        "import_title" => "Import an Index",
         "sidebar_title" => "Containers",
         "sidebar_collapse" => "Collapse sidebar",
         "sidebar_expand" => "Expand sidebar",
@@ -78,6 +80,10 @@ fn en(key: &str) -> &'static str {
         "sidebar_rebuild" => "Rebuild Index",
         "sidebar_rebuild_tooltip" => "Re-index all folders with improved embeddings",
         "sidebar_clear" => "Clear Index",
+        "sidebar_prune" => "Remove Missing Files",
+        "sidebar_check" => "Check Index",
+        "sidebar_export" => "Export Index...",
+        "sidebar_import" => "Import Index...",
         "sidebar_clear_tooltip" => "Remove all indexed data from this container",
         "sidebar_delete" => "Delete Container",
         "results_no_preview" => "No preview available",
@@ -107,19 +113,47 @@ fn en(key: &str) -> &'static str {
         "dialog_rebuild_title" => "Rebuild Index",
         "dialog_rebuild_message" => "This will re-index all {{count}} folder(s) in '{{name}}' with improved embeddings. This may take a moment.",
         "dialog_rebuild_confirm" => "Rebuild",
+        "dialog_remove_folder_title" => "Remove Folder",
+        "dialog_remove_folder_message" => "Remove '{{path}}' from '{{name}}'? Its files will be dropped from the index. Nothing is deleted from disk.",
+        "dialog_remove_folder_confirm" => "Remove",
+        "dialog_schema_rebuild_title" => "Index Needs Rebuilding",
+        "dialog_schema_rebuild_message" => "The index of '{{name}}' can't be updated in place: {{reason}}. Rebuilding drops it and re-indexes every folder. Nothing is deleted from disk.",
+        "dialog_schema_rebuild_confirm" => "Rebuild",
+        "dialog_repair_title" => "Index Problems",
+        "dialog_repair_message" => "Found {{count}} problem(s):\n{{issues}}\n\nRepair drops leftover tables, rows outside every folder and broken vectors, and builds missing indexes. Nothing is deleted from disk.",
+        "dialog_repair_confirm" => "Repair",
         "status_switched" => "Switched to {{name}}",
         "status_clearing" => "Clearing index...",
+        "status_pruning" => "Removing missing files...",
+        "status_checking" => "Checking index...",
+        "status_check_ok" => "No problems found.",
+        "status_repairing" => "Repairing index...",
+        "status_exporting" => "Exporting index...",
+        "status_importing" => "Importing index...",
         "status_cleared" => "Index cleared.",
         "status_rebuilding" => "Rebuilding index...",
+        "status_reembedding" => "Re-embedding {{name}} with the new model: {{current}}/{{total}} chunks",
         "status_starting" => "Starting indexing...",
         "status_indexing_file" => "Indexing: {{filename}}",
         "status_result_count" => "{{count}} results",
         "status_done" => "Done -- {{message}}",
         "status_rebuild_needed" => "Index needs rebuild -- click Rebuild Index",
+        "status_search_skipped" => "Not searched, indexed with another model: {{containers}}",
         "status_model_error" => "Model Error: {{error}}",
         "status_model_loading" => "Loading AI model...",
+        "status_paused" => "Paused",
+        "status_cancelling" => "Cancelling...",
+        "job_pause" => "Pause indexing",
+        "job_resume" => "Resume indexing",
+        "job_cancel" => "Cancel indexing",
+        "report_timings" => "walk {{walk}} · embed {{embed}} · total {{total}}",
+        "report_failed" => "Failed:",
+        "report_skipped" => "Skipped:",
+        "report_more" => "  ...and {{count}} more",
         "settings_title" => "Settings",
         "settings_add_folder" => "Add Folder (Ctrl+O)",
+        "settings_remove_folder" => "Remove folder",
+        "settings_search_also" => "Also search this container",
         "settings_containers_section" => "Containers",
         "settings_folders_section" => "Indexed Folders",
         _ => "???",
@@ -130,6 +164,8 @@ fn tr(key: &str) -> &'static str {
     match key {
         "search_placeholder" => "{{container}} icinde ara...",
         "index_folder_title" => "{{container}} icin klasor indexle (Ctrl+O)",
+        "export_title" => "{{container}} disa aktar",
+        "import_title" => "Index ice aktar",
         "sidebar_title" => "Konteynerler",
         "sidebar_collapse" => "Kenar cubugunu daralt",
         "sidebar_expand" => "Kenar cubugunu genislet",
@@ -139,6 +175,10 @@ fn tr(key: &str) -> &'static str {
         "sidebar_rebuild" => "Indexi Yeniden Olustur",
         "sidebar_rebuild_tooltip" => "Tum klasorleri gelistirilmis embeddinglerle yeniden indexle",
         "sidebar_clear" => "Indexi Temizle",
+        "sidebar_prune" => "Eksik Dosyalari Kaldir",
+        "sidebar_check" => "Indexi Denetle",
+        "sidebar_export" => "Indexi Disa Aktar...",
+        "sidebar_import" => "Index Ice Aktar...",
         "sidebar_clear_tooltip" => "Bu konteynerdeki tum indexlenmis verileri kaldir",
         "sidebar_delete" => "Konteyneri Sil",
         "results_no_preview" => "Onizleme yok",
@@ -168,19 +208,47 @@ fn tr(key: &str) -> &'static str {
         "dialog_rebuild_title" => "Indexi Yeniden Olustur",
         "dialog_rebuild_message" => "Bu islem '{{name}}' icindeki {{count}} klasoru gelistirilmis embeddinglerle yeniden indexleyecek. Biraz zaman alabilir.",
         "dialog_rebuild_confirm" => "Yeniden Olustur",
+        "dialog_remove_folder_title" => "Klasoru Kaldir",
+        "dialog_remove_folder_message" => "'{{path}}' klasoru '{{name}}' konteynerinden kaldirilsin mi? Dosyalari indexten cikarilacak. Diskten hicbir sey silinmez.",
+        "dialog_remove_folder_confirm" => "Kaldir",
+        "dialog_schema_rebuild_title" => "Index Yeniden Olusturulmali",
+        "dialog_schema_rebuild_message" => "'{{name}}' indexi yerinde guncellenemiyor: {{reason}}. Yeniden olusturmak indexi silip tum klasorleri yeniden indexler. Diskten hicbir sey silinmez.",
+        "dialog_schema_rebuild_confirm" => "Yeniden Olustur",
+        "dialog_repair_title" => "Index Sorunlari",
+        "dialog_repair_message" => "{{count}} sorun bulundu:\n{{issues}}\n\nOnarim artik tablolari, hicbir klasore ait olmayan satirlari ve bozuk vektorleri siler, eksik indexleri olusturur. Diskten hicbir sey silinmez.",
+        "dialog_repair_confirm" => "Onar",
         "status_switched" => "{{name}} konteynerine gecildi",
         "status_clearing" => "Index temizleniyor...",
+        "status_pruning" => "Eksik dosyalar kaldiriliyor...",
+        "status_checking" => "Index denetleniyor...",
+        "status_check_ok" => "Sorun bulunamadi.",
+        "status_repairing" => "Index onariliyor...",
+        "status_exporting" => "Index disa aktariliyor...",
+        "status_importing" => "Index ice aktariliyor...",
         "status_cleared" => "Index temizlendi.",
         "status_rebuilding" => "Index yeniden olusturuluyor...",
+        "status_reembedding" => "{{name}} yeni modelle yeniden embed ediliyor: {{current}}/{{total}} parca",
         "status_starting" => "Indexleme basliyor...",
         "status_indexing_file" => "Indexleniyor: {{filename}}",
         "status_result_count" => "{{count}} sonuc",
         "status_done" => "Tamamlandi -- {{message}}",
         "status_rebuild_needed" => "Index yeniden olusturulmali -- Yeniden Olustur'a tiklayin",
+        "status_search_skipped" => "Aranmadi, baska bir modelle indexlendi: {{containers}}",
         "status_model_error" => "Model Hatasi: {{error}}",
         "status_model_loading" => "AI modeli yukleniyor...",
+        "status_paused" => "Duraklatildi",
+        "status_cancelling" => "Iptal ediliyor...",
+        "job_pause" => "Indexlemeyi duraklat",
+        "job_resume" => "Indexlemeye devam et",
+        "job_cancel" => "Indexlemeyi iptal et",
+        "report_timings" => "tarama {{walk}} · embedding {{embed}} · toplam {{total}}",
+        "report_failed" => "Basarisiz:",
+        "report_skipped" => "Atlanan:",
+        "report_more" => "  ...ve {{count}} tane daha",
         "settings_title" => "Ayarlar",
         "settings_add_folder" => "Klasor Ekle (Ctrl+O)",
+        "settings_remove_folder" => "Klasoru kaldir",
+        "settings_search_also" => "Bu konteynerde de ara",
         "settings_containers_section" => "Konteynerler",
         "settings_folders_section" => "Indexlenen Klasorler",
         _ => "",
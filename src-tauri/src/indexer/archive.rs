@@ -0,0 +1,258 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use arrow_array::{RecordBatch, RecordBatchIterator};
+use arrow_ipc::reader::FileReader;
+use arrow_ipc::writer::FileWriter;
+use arrow_schema::{DataType, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::ExecutableQuery;
+use serde::{Deserialize, Serialize};
+
+use super::embedding::ModelIdentity;
+use super::{db, maintenance, paths, schema};
+use crate::config::ContainerInfo;
+
+/// Bumped when the archive layout changes in a way older builds can't read.
+pub const ARCHIVE_VERSION: u32 = 1;
+pub const ARCHIVE_EXTENSION: &str = "recall";
+
+/// Schema metadata key holding the manifest, so an archive stays a single Arrow IPC file.
+const MANIFEST_KEY: &str = "recall.manifest";
+
+/// Everything needed to check an archive before a single row is imported.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub archive_version: u32,
+    pub container: String,
+    pub info: ContainerInfo,
+    pub model: ModelIdentity,
+    pub dim: usize,
+    pub schema_version: i32,
+    pub chunks: usize,
+    /// Unix seconds.
+    pub exported_at: i64,
+}
+
+fn vector_dim(schema: &Schema) -> Option<usize> {
+    match schema.field_with_name("vector").ok()?.data_type() {
+        DataType::FixedSizeList(_, size) => Some(*size as usize),
+        _ => None,
+    }
+}
+
+/// Writes every row of `table_name` to `dest`, its manifest in the schema metadata.
+/// The file appears only once complete.
+pub async fn export_table(
+    db: &Connection,
+    table_name: &str,
+    container: &str,
+    info: ContainerInfo,
+    model: &ModelIdentity,
+    dest: &Path,
+) -> Result<ArchiveManifest> {
+    let table = db.open_table(table_name).execute().await?;
+    let table_schema = table.schema().await?;
+    let manifest = ArchiveManifest {
+        archive_version: ARCHIVE_VERSION,
+        container: container.to_string(),
+        info,
+        model: model.clone(),
+        dim: vector_dim(&table_schema).ok_or_else(|| anyhow!("Index has no vector column"))?,
+        schema_version: schema::SCHEMA_VERSION,
+        chunks: table.count_rows(None).await?,
+        exported_at: chrono::Utc::now().timestamp(),
+    };
+
+    let file_schema = Arc::new(Schema::new_with_metadata(
+        table_schema.fields().clone(),
+        [(MANIFEST_KEY.to_string(), serde_json::to_string(&manifest)?)].into(),
+    ));
+    let tmp = dest.with_extension(format!("{}.tmp", ARCHIVE_EXTENSION));
+    let mut writer = FileWriter::try_new(BufWriter::new(File::create(&tmp)?), &file_schema)?;
+    let mut stream = table.query().execute().await?;
+    while let Some(batch) = stream.try_next().await? {
+        writer.write(&RecordBatch::try_new(file_schema.clone(), batch.columns().to_vec())?)?;
+    }
+    writer.finish()?;
+    drop(writer);
+    std::fs::rename(&tmp, dest)?;
+
+    Ok(manifest)
+}
+
+fn open(path: &Path) -> Result<FileReader<BufReader<File>>> {
+    FileReader::try_new(BufReader::new(File::open(path)?), None)
+        .map_err(|e| anyhow!("Not a Recall-Lite archive: {}", e))
+}
+
+fn manifest_of(file_schema: &Schema) -> Result<ArchiveManifest> {
+    let json = file_schema
+        .metadata()
+        .get(MANIFEST_KEY)
+        .ok_or_else(|| anyhow!("Not a Recall-Lite archive: manifest missing"))?;
+    Ok(serde_json::from_str(json)?)
+}
+
+pub fn read_manifest(path: &Path) -> Result<ArchiveManifest> {
+    manifest_of(&open(path)?.schema())
+}
+
+/// Refuses archives this install can't search: newer layout, or vectors from another model.
+fn validate(
+    manifest: &ArchiveManifest,
+    file_schema: &Schema,
+    model: &ModelIdentity,
+    dim: usize,
+) -> Result<()> {
+    if manifest.archive_version > ARCHIVE_VERSION || manifest.schema_version > schema::SCHEMA_VERSION {
+        return Err(anyhow!("Archive was written by a newer version of Recall-Lite"));
+    }
+    if &manifest.model != model {
+        return Err(anyhow!(
+            "Archive was built with {}, this install uses {}. Set embedding_model to {} to import it.",
+            manifest.model.describe(),
+            model.describe(),
+            manifest.model.name
+        ));
+    }
+    let file_dim = vector_dim(file_schema);
+    if file_dim != Some(dim) || manifest.dim != dim {
+        return Err(anyhow!(
+            "Archive vectors have {} dimensions, the current model produces {}",
+            file_dim.unwrap_or(0),
+            dim
+        ));
+    }
+    Ok(())
+}
+
+/// Creates `table_name` from the archive at `src`, then brings it to the current schema
+/// and rebuilds its indexes. Fails without touching anything if the table exists, and
+/// drops what it created if any step fails.
+pub async fn import_table(
+    db: &Connection,
+    table_name: &str,
+    src: &Path,
+    model: &ModelIdentity,
+    dim: usize,
+) -> Result<ArchiveManifest> {
+    let reader = open(src)?;
+    let file_schema = reader.schema();
+    let manifest = manifest_of(&file_schema)?;
+    validate(&manifest, &file_schema, model, dim)?;
+    if db.open_table(table_name).execute().await.is_ok() {
+        return Err(anyhow!("Index '{}' already exists", table_name));
+    }
+
+    // The manifest stays in the archive, not in the table
+    let table_schema = Arc::new(Schema::new(file_schema.fields().clone()));
+    let batch_schema = table_schema.clone();
+    let batches = reader.map(move |batch| {
+        batch.and_then(|b| RecordBatch::try_new(batch_schema.clone(), b.columns().to_vec()))
+    });
+    schema::clear_meta(db, table_name).await?;
+    let created = db
+        .create_table(table_name, RecordBatchIterator::new(batches, table_schema))
+        .execute()
+        .await;
+    let finished = match created {
+        Ok(_) => finish_import(db, table_name, &manifest, model, dim).await,
+        Err(e) => Err(e.into()),
+    };
+    // A half-imported table would otherwise block the next attempt
+    if let Err(e) = finished {
+        let _ = db.drop_table(table_name, &[]).await;
+        let _ = schema::clear_meta(db, table_name).await;
+        return Err(e);
+    }
+    Ok(manifest)
+}
+
+/// Records what the archive was built with, migrates the new table and rebuilds its indexes.
+async fn finish_import(
+    db: &Connection,
+    table_name: &str,
+    manifest: &ArchiveManifest,
+    model: &ModelIdentity,
+    dim: usize,
+) -> Result<()> {
+    schema::set_version(db, table_name, manifest.schema_version).await?;
+    schema::set_model(db, table_name, &manifest.model).await?;
+    let vectors = &manifest.info.vectors;
+    let table = db::get_or_create_table(db, table_name, dim, model, vectors).await?;
+    // Archives written before roots had ids carry absolute paths
+    let roots = paths::PathMap::new(manifest.info.indexed_paths.clone());
+    paths::rebase(&table, &roots, &roots).await?;
+    maintenance::refresh_indexes(&table, vectors).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::VectorPrecision;
+
+    fn model(name: &str) -> ModelIdentity {
+        ModelIdentity {
+            name: name.to_string(),
+            revision: format!("{}/model.onnx", name),
+            passage_prefix: "passage: ".to_string(),
+            query_prefix: "query: ".to_string(),
+        }
+    }
+
+    fn manifest(model: ModelIdentity, dim: usize) -> ArchiveManifest {
+        ArchiveManifest {
+            archive_version: ARCHIVE_VERSION,
+            container: "Work".to_string(),
+            info: ContainerInfo {
+                description: String::new(),
+                indexed_paths: vec![crate::config::IndexedRoot {
+                    id: "a1b2c3d4".to_string(),
+                    path: "/src".to_string(),
+                }],
+                globs: Vec::new(),
+                vectors: Default::default(),
+                fusion: Default::default(),
+            },
+            model,
+            dim,
+            schema_version: schema::SCHEMA_VERSION,
+            chunks: 10,
+            exported_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_matching_archive() {
+        let archive = manifest(model("MultilingualE5Base"), 768);
+        assert!(validate(&archive, &db::make_schema(768, VectorPrecision::Float32), &model("MultilingualE5Base"), 768).is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_other_model_of_same_dimension() {
+        let archive = manifest(model("MultilingualE5Small"), 384);
+        let err = validate(&archive, &db::make_schema(384, VectorPrecision::Float32), &model("AllMiniLML6V2"), 384).unwrap_err();
+        assert!(err.to_string().contains("MultilingualE5Small"));
+    }
+
+    #[test]
+    fn test_validate_rejects_dimension_mismatch() {
+        let archive = manifest(model("MultilingualE5Base"), 768);
+        assert!(validate(&archive, &db::make_schema(384, VectorPrecision::Float32), &model("MultilingualE5Base"), 768).is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_newer_archive() {
+        let archive = ArchiveManifest {
+            archive_version: ARCHIVE_VERSION + 1,
+            ..manifest(model("MultilingualE5Base"), 768)
+        };
+        assert!(validate(&archive, &db::make_schema(768, VectorPrecision::Float32), &model("MultilingualE5Base"), 768).is_err());
+    }
+}
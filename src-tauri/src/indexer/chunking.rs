@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use regex::Regex;
 
 pub struct ChunkConfig {
@@ -104,12 +106,41 @@ fn get_semantic_pattern(ext: &str) -> Option<Regex> {
     Regex::new(pattern).ok()
 }
 
+/// A chunk of extracted text and where it sits in it. Bytes are a half-open range,
+/// lines are 1-based and inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk {
+    pub content: String,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
 pub fn semantic_chunk_with_overrides(
     text: &str,
     ext: &str,
     chunk_size: Option<usize>,
     chunk_overlap: Option<usize>,
 ) -> Vec<String> {
+    chunk_spans(text, ext, chunk_size, chunk_overlap)
+        .into_iter()
+        .map(|c| c.content)
+        .collect()
+}
+
+pub fn semantic_chunk(text: &str, ext: &str) -> Vec<String> {
+    semantic_chunk_with_overrides(text, ext, None, None)
+}
+
+/// Same chunking as [`semantic_chunk_with_overrides`], keeping each chunk's position.
+/// Every chunk is a verbatim slice of `text`.
+pub fn chunk_spans(
+    text: &str,
+    ext: &str,
+    chunk_size: Option<usize>,
+    chunk_overlap: Option<usize>,
+) -> Vec<Chunk> {
     let mut config = get_chunk_config(ext);
     if let Some(size) = chunk_size {
         config.max_bytes = size.max(100);
@@ -118,26 +149,29 @@ pub fn semantic_chunk_with_overrides(
         config.overlap_bytes = overlap;
     }
 
-    let pattern = match get_semantic_pattern(ext) {
-        Some(p) => p,
-        None => return chunk_with_overlap(text, config.max_bytes, config.overlap_bytes),
-    };
-
-    chunk_with_semantic_config(text, &config, &pattern)
-}
-
-pub fn semantic_chunk(text: &str, ext: &str) -> Vec<String> {
-    let config = get_chunk_config(ext);
-
-    let pattern = match get_semantic_pattern(ext) {
-        Some(p) => p,
-        None => return chunk_with_overlap(text, config.max_bytes, config.overlap_bytes),
+    let spans = match get_semantic_pattern(ext) {
+        Some(pattern) => semantic_spans(text, &config, &pattern),
+        None => overlap_spans(text, 0, text.len(), config.max_bytes, config.overlap_bytes),
     };
 
-    chunk_with_semantic_config(text, &config, &pattern)
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+        .collect();
+    let line_of = |byte: usize| line_starts.partition_point(|&start| start <= byte);
+
+    spans
+        .into_iter()
+        .map(|span| Chunk {
+            content: text[span.clone()].to_string(),
+            start_line: line_of(span.start),
+            end_line: line_of(span.end.saturating_sub(1).max(span.start)),
+            start_byte: span.start,
+            end_byte: span.end,
+        })
+        .collect()
 }
 
-fn chunk_with_semantic_config(text: &str, config: &ChunkConfig, pattern: &Regex) -> Vec<String> {
+fn semantic_spans(text: &str, config: &ChunkConfig, pattern: &Regex) -> Vec<Range<usize>> {
     let mut split_points: Vec<usize> = vec![0];
     for m in pattern.find_iter(text) {
         let pos = m.start();
@@ -151,67 +185,96 @@ fn chunk_with_semantic_config(text: &str, config: &ChunkConfig, pattern: &Regex)
     split_points.push(text.len());
     split_points.dedup();
 
-    let mut chunks: Vec<String> = Vec::new();
-    let mut current = String::new();
-    let mut prev_last_line = String::new();
+    let mut spans: Vec<Range<usize>> = Vec::new();
+    let mut current = 0..0;
 
     for window in split_points.windows(2) {
-        let segment = &text[window[0]..window[1]];
-
-        if !current.is_empty() && current.len() + segment.len() > config.max_bytes {
-            if current.len() > config.max_bytes {
-                let sub_chunks =
-                    chunk_with_overlap(&current, config.max_bytes, config.overlap_bytes);
-                if let Some(last) = sub_chunks.last() {
-                    prev_last_line = last.lines().last().unwrap_or("").to_string();
-                }
-                chunks.extend(sub_chunks);
+        let segment_len = window[1] - window[0];
+
+        if !current.is_empty() && current.len() + segment_len > config.max_bytes {
+            let last = if current.len() > config.max_bytes {
+                let sub_spans = overlap_spans(
+                    text,
+                    current.start,
+                    current.end,
+                    config.max_bytes,
+                    config.overlap_bytes,
+                );
+                let last = sub_spans.last().cloned().unwrap_or_else(|| current.clone());
+                spans.extend(sub_spans);
+                last
             } else {
-                prev_last_line = current.lines().last().unwrap_or("").to_string();
-                chunks.push(current.clone());
-            }
-            current.clear();
-            if !prev_last_line.is_empty() {
-                current.push_str(&prev_last_line);
-                current.push('\n');
-            }
+                spans.push(current.clone());
+                current.clone()
+            };
+            // The last line of the previous chunk is repeated for context
+            current = carried_line_start(text, last)..current.end;
         }
 
-        current.push_str(segment);
+        current.end = window[1];
     }
 
-    if !current.trim().is_empty() {
+    if !text[current.clone()].trim().is_empty() {
         if current.len() > config.max_bytes {
-            chunks.extend(chunk_with_overlap(
-                &current,
+            spans.extend(overlap_spans(
+                text,
+                current.start,
+                current.end,
                 config.max_bytes,
                 config.overlap_bytes,
             ));
         } else {
-            chunks.push(current);
+            spans.push(current);
         }
     }
 
-    if chunks.is_empty() {
-        chunks.push(text.to_string());
+    if spans.is_empty() {
+        spans.push(0..text.len());
     }
 
-    chunks
+    spans
 }
 
-pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) -> Vec<String> {
-    let mut chunks = Vec::new();
-    let mut start = 0;
+/// Start of the last line of `span`, or its end when that line is blank.
+fn carried_line_start(text: &str, span: Range<usize>) -> usize {
+    let body = &text[span.clone()];
+    let body = body.strip_suffix('\n').unwrap_or(body);
+    let line_start = body.rfind('\n').map(|i| span.start + i + 1).unwrap_or(span.start);
+    if text[line_start..span.start + body.len()].trim().is_empty() {
+        span.end
+    } else {
+        line_start
+    }
+}
 
-    while start < text.len() {
-        let mut end = (start + max_bytes).min(text.len());
+pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) -> Vec<String> {
+    overlap_spans(text, 0, text.len(), max_bytes, overlap_bytes)
+        .into_iter()
+        .map(|span| text[span].to_string())
+        .collect()
+}
 
-        while end < text.len() && !text.is_char_boundary(end) {
+/// Splits `text[from..to]` into windows of at most `max_bytes`, cut at a line, sentence or
+/// word break when there is one, each starting `overlap_bytes` before the previous end.
+fn overlap_spans(
+    text: &str,
+    from: usize,
+    to: usize,
+    max_bytes: usize,
+    overlap_bytes: usize,
+) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut start = from;
+
+    while start < to {
+        let mut end = (start + max_bytes).min(to);
+
+        while end < to && !text.is_char_boundary(end) {
             end -= 1;
         }
 
-        if end >= text.len() {
-            chunks.push(text[start..].to_string());
+        if end >= to {
+            spans.push(start..to);
             break;
         }
 
@@ -223,7 +286,7 @@ pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) ->
             .map(|i| start + i + 1)
             .unwrap_or(end);
 
-        chunks.push(text[start..split_at].to_string());
+        spans.push(start..split_at);
 
         let rewind = overlap_bytes.min(split_at - start);
         let mut overlap_start = split_at - rewind;
@@ -236,7 +299,14 @@ pub fn chunk_with_overlap(text: &str, max_bytes: usize, overlap_bytes: usize) ->
         start = overlap_start;
     }
 
-    chunks
+    spans
+}
+
+/// Cache key for a chunk's embedding: BLAKE3 of the text with whitespace runs collapsed,
+/// so re-indented or re-wrapped chunks still hit the stored vector.
+pub fn chunk_hash(text: &str) -> String {
+    let normalized = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    blake3::hash(normalized.as_bytes()).to_hex().to_string()
 }
 
 const STOP_WORDS: &[&str] = &[
@@ -357,6 +427,15 @@ mod tests {
         assert_eq!(chunks.len(), 1);
     }
 
+    #[test]
+    fn test_chunk_hash_ignores_whitespace_layout() {
+        assert_eq!(
+            chunk_hash("fn main() {\n    run();\n}"),
+            chunk_hash("fn main() {\n\trun();\n}\n")
+        );
+        assert_ne!(chunk_hash("fn main() {}"), chunk_hash("fn main2() {}"));
+    }
+
     #[test]
     fn test_expand_query_basic() {
         let variants = expand_query("Hello World");
@@ -398,6 +477,32 @@ mod tests {
         assert_eq!(default_chunks, override_chunks);
     }
 
+    #[test]
+    fn test_chunk_spans_point_into_text() {
+        let code = format!(
+            "use std::io;\n\nfn main() {{\n{}}}\n\npub fn helper() {{\n    let x = 1;\n}}\n",
+            "    run();\n".repeat(200)
+        );
+        let chunks = chunk_spans(&code, "rs", None, None);
+        assert!(chunks.len() >= 2);
+        for chunk in &chunks {
+            assert_eq!(chunk.content, &code[chunk.start_byte..chunk.end_byte]);
+            let first_line = code[..chunk.start_byte].matches('\n').count() + 1;
+            assert_eq!(chunk.start_line, first_line);
+            assert!(chunk.end_line >= chunk.start_line);
+        }
+        let helper = chunks.iter().find(|c| c.content.contains("helper")).unwrap();
+        assert_eq!(helper.end_line, code.lines().count());
+    }
+
+    #[test]
+    fn test_chunk_spans_lines_single_chunk() {
+        let chunks = chunk_spans("one\ntwo\nthree\n", "xyz", None, None);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!((chunks[0].start_line, chunks[0].end_line), (1, 3));
+        assert_eq!((chunks[0].start_byte, chunks[0].end_byte), (0, 14));
+    }
+
     #[test]
     fn test_override_custom_values() {
         let text = "a".repeat(1000);
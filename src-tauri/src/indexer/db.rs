@@ -4,26 +4,86 @@ use std::sync::Arc;
 
 use anyhow::{anyhow, Result};
 use arrow_array::{
-    Float32Array, FixedSizeListArray, Int64Array, RecordBatch, RecordBatchIterator, StringArray,
+    Array, Float16Array, Float32Array, FixedSizeListArray, Int32Array, Int64Array, RecordBatch,
+    RecordBatchIterator, StringArray,
 };
 use arrow_schema::{DataType, Field, Schema};
 use futures::TryStreamExt;
 use lancedb::connection::Connection;
+use lancedb::index::vector::{
+    IvfFlatIndexBuilder, IvfHnswPqIndexBuilder, IvfHnswSqIndexBuilder, IvfPqIndexBuilder,
+};
 use lancedb::index::Index;
 use lancedb::query::{ExecutableQuery, QueryBase};
-use lancedb::Table;
+use lancedb::{DistanceType, Table};
+use serde::Serialize;
+
+use super::embedding::ModelIdentity;
+use super::filter::{Filter, Value};
+use super::schema::{self, RebuildRequired};
+use crate::config::{AnnIndexType, VectorConfig, VectorPrecision};
+
+const DELETE_BATCH_SIZE: usize = 500;
+
+/// Where a chunk sits in its file, and the file as it was when the chunk was written.
+/// Byte and line ranges refer to the extracted text (PDF text, OCR output...), which for
+/// plain text files is the file itself.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChunkMeta {
+    pub chunk_index: i32,
+    pub start_byte: i64,
+    pub end_byte: i64,
+    pub start_line: i32,
+    pub end_line: i32,
+    pub file_size: i64,
+    /// Lowercase, without the dot. Empty for files like `Dockerfile`.
+    pub extension: String,
+    pub mtime: i64,
+    /// Unix seconds.
+    pub indexed_at: i64,
+}
 
 pub struct Record {
     pub path: String,
+    pub chunk_hash: String,
     pub content: String,
     pub vector: Vec<f32>,
-    pub mtime: i64,
+    pub content_hash: String,
+    pub meta: ChunkMeta,
 }
 
 pub struct PendingChunk {
     pub path: String,
     pub content: String,
+    pub content_hash: String,
+    /// `indexed_at` is stamped when the batch is written.
+    pub meta: ChunkMeta,
+}
+
+/// What the index remembers about a file, used to decide whether it needs re-embedding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileFingerprint {
     pub mtime: i64,
+    pub file_size: i64,
+    pub content_hash: String,
+}
+
+impl FileFingerprint {
+    /// Same mtime and size. With `quick_check` on, taken as unchanged without reading
+    /// the file; otherwise the file is always hashed and checked with [`Self::is_unchanged`].
+    pub fn same_stat(&self, mtime: i64, file_size: i64) -> bool {
+        self.mtime == mtime && self.file_size == file_size
+    }
+
+    /// The content hash is authoritative: a touched-but-identical file is unchanged,
+    /// an edited file with a preserved mtime is not. Rows without a hash fall back to mtime.
+    pub fn is_unchanged(&self, mtime: i64, content_hash: &str) -> bool {
+        if self.content_hash.is_empty() {
+            self.mtime == mtime
+        } else {
+            self.content_hash == content_hash
+        }
+    }
 }
 
 pub async fn reset_index(db_path: &Path, table_name: &str) -> Result<()> {
@@ -31,12 +91,34 @@ pub async fn reset_index(db_path: &Path, table_name: &str) -> Result<()> {
         .execute()
         .await?;
     let _ = db.drop_table(table_name, &[]).await;
+    schema::clear_meta(&db, table_name).await?;
     Ok(())
 }
 
-pub async fn build_ann_index(table: &Table) -> Result<()> {
+/// Explicit index types are trained for cosine distance, the metric every search uses.
+pub async fn build_ann_index(table: &Table, vectors: &VectorConfig) -> Result<()> {
+    let index = match vectors.index {
+        AnnIndexType::Auto => Index::Auto,
+        AnnIndexType::IvfFlat => {
+            Index::IvfFlat(IvfFlatIndexBuilder::default().distance_type(DistanceType::Cosine))
+        }
+        AnnIndexType::IvfPq => {
+            Index::IvfPq(IvfPqIndexBuilder::default().distance_type(DistanceType::Cosine))
+        }
+        AnnIndexType::IvfHnswPq => {
+            Index::IvfHnswPq(IvfHnswPqIndexBuilder::default().distance_type(DistanceType::Cosine))
+        }
+        AnnIndexType::IvfHnswSq => {
+            Index::IvfHnswSq(IvfHnswSqIndexBuilder::default().distance_type(DistanceType::Cosine))
+        }
+    };
+    table.create_index(&["vector"], index).execute().await?;
+    Ok(())
+}
+
+pub async fn build_chunk_hash_index(table: &Table) -> Result<()> {
     table
-        .create_index(&["vector"], Index::Auto)
+        .create_index(&["chunk_hash"], Index::BTree(Default::default()))
         .execute()
         .await?;
     Ok(())
@@ -50,29 +132,238 @@ pub async fn build_fts_index(table: &Table) -> Result<()> {
     Ok(())
 }
 
-pub async fn get_single_file_mtime(table: &Table, file_path: &str) -> Result<Option<i64>> {
-    let safe_path = file_path.replace('\'', "''");
+/// Replaces every chunk of `paths` with `records` in a single commit.
+///
+/// Rows are keyed by `(path, chunk_index)`; chunks of `paths` missing from `records`
+/// are deleted in the same transaction, so a file never disappears from the index
+/// while its new version is being written.
+pub async fn replace_files(table: &Table, paths: &[String], records: Vec<Record>) -> Result<()> {
+    if paths.is_empty() {
+        return Ok(());
+    }
+    if records.is_empty() {
+        table.delete(&Filter::path_in(paths).to_sql()).await?;
+        return Ok(());
+    }
+
+    let batch = create_record_batch(records, table_precision(table).await?)?;
+    let schema = batch.schema();
+    let mut merge = table.merge_insert(&["path", "chunk_index"]);
+    merge
+        .when_matched_update_all(None)
+        .when_not_matched_insert_all()
+        .when_not_matched_by_source_delete(Some(Filter::path_in(paths).to_sql()));
+    merge
+        .execute(Box::new(RecordBatchIterator::new(vec![Ok(batch)], schema)))
+        .await?;
+    Ok(())
+}
+
+/// Appends rows as they are, for a table being filled from scratch.
+pub async fn append_records(table: &Table, records: Vec<Record>) -> Result<()> {
+    if records.is_empty() {
+        return Ok(());
+    }
+    let batch = create_record_batch(records, table_precision(table).await?)?;
+    let schema = batch.schema();
+    table
+        .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
+        .execute()
+        .await?;
+    Ok(())
+}
+
+/// Deletes every chunk of `paths`, in slices small enough to keep the predicate reasonable.
+pub async fn delete_paths(table: &Table, paths: &[String]) -> Result<()> {
+    for slice in paths.chunks(DELETE_BATCH_SIZE) {
+        table.delete(&Filter::path_in(slice).to_sql()).await?;
+    }
+    Ok(())
+}
+
+/// Records the new mtime of files touched without being changed, rows otherwise kept.
+/// Each slice of paths is updated in a single commit.
+pub async fn update_mtimes(table: &Table, mtimes: &HashMap<String, i64>) -> Result<()> {
+    let entries: Vec<(&String, &i64)> = mtimes.iter().collect();
+    for slice in entries.chunks(DELETE_BATCH_SIZE) {
+        let paths: Vec<String> = slice.iter().map(|(path, _)| path.to_string()).collect();
+        let cases: Vec<String> = slice
+            .iter()
+            .map(|(path, mtime)| format!("WHEN {} THEN {}", Filter::path_is(path).to_sql(), mtime))
+            .collect();
+        table
+            .update()
+            .only_if(Filter::path_in(&paths).to_sql())
+            .column("mtime", format!("CASE {} ELSE mtime END", cases.join(" ")))
+            .execute()
+            .await?;
+    }
+    Ok(())
+}
+
+/// Moves every chunk of each `old -> new` path to its new path, vector and metadata
+/// untouched. Each slice of paths is moved in a single commit.
+pub async fn rename_paths(table: &Table, renames: &HashMap<String, String>) -> Result<()> {
+    let old: Vec<String> = renames.keys().cloned().collect();
+    for slice in old.chunks(DELETE_BATCH_SIZE) {
+        let batches = table
+            .query()
+            .only_if(Filter::path_in(slice).to_sql())
+            .execute()
+            .await?
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        let mut renamed = Vec::with_capacity(batches.len());
+        for batch in batches {
+            let column = batch.schema().index_of("path")?;
+            let paths = batch
+                .column(column)
+                .as_any()
+                .downcast_ref::<StringArray>()
+                .ok_or_else(|| anyhow!("path column is not a string"))?;
+            let new_paths: StringArray = paths
+                .iter()
+                .map(|p| p.map(|p| renames.get(p).map_or(p, |n| n.as_str())))
+                .collect();
+            let mut columns = batch.columns().to_vec();
+            columns[column] = Arc::new(new_paths);
+            renamed.push(RecordBatch::try_new(batch.schema(), columns)?);
+        }
+        let Some(schema) = renamed.first().map(|b| b.schema()) else {
+            continue;
+        };
+
+        let mut merge = table.merge_insert(&["path", "chunk_index"]);
+        merge
+            .when_not_matched_insert_all()
+            .when_not_matched_by_source_delete(Some(Filter::path_in(slice).to_sql()));
+        merge
+            .execute(Box::new(RecordBatchIterator::new(
+                renamed.into_iter().map(Ok),
+                schema,
+            )))
+            .await?;
+    }
+    Ok(())
+}
+
+fn fingerprints_from_batch(batch: &RecordBatch, out: &mut HashMap<String, FileFingerprint>) {
+    let path_array = batch
+        .column_by_name("path")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+    let mtime_array = batch
+        .column_by_name("mtime")
+        .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+    let hash_array = batch
+        .column_by_name("content_hash")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+    let size_array = batch
+        .column_by_name("file_size")
+        .and_then(|c| c.as_any().downcast_ref::<Int64Array>());
+
+    if let (Some(paths), Some(mtimes), Some(hashes)) = (path_array, mtime_array, hash_array) {
+        for i in 0..batch.num_rows() {
+            out.insert(
+                paths.value(i).to_string(),
+                FileFingerprint {
+                    mtime: mtimes.value(i),
+                    // Unknown size never matches, so the file gets hashed
+                    file_size: size_array.map_or(-1, |s| s.value(i)),
+                    content_hash: hashes.value(i).to_string(),
+                },
+            );
+        }
+    }
+}
+
+fn fingerprint_columns() -> lancedb::query::Select {
+    lancedb::query::Select::Columns(vec![
+        "path".to_string(),
+        "mtime".to_string(),
+        "file_size".to_string(),
+        "content_hash".to_string(),
+    ])
+}
+
+/// Looks up stored vectors for the given chunk hashes. Hashes with no stored row are absent.
+pub async fn get_cached_vectors(
+    table: &Table,
+    chunk_hashes: &[String],
+) -> Result<HashMap<String, Vec<f32>>> {
+    let mut cached = HashMap::new();
+    if chunk_hashes.is_empty() {
+        return Ok(cached);
+    }
+
+    let hashes: Vec<Value> = chunk_hashes.iter().map(|h| h.as_str().into()).collect();
     let results = table
         .query()
-        .only_if(format!("path = '{}'", safe_path))
-        .select(lancedb::query::Select::Columns(vec!["mtime".to_string()]))
-        .limit(1)
+        .only_if(Filter::In("chunk_hash", hashes).to_sql())
+        .select(lancedb::query::Select::Columns(vec![
+            "chunk_hash".to_string(),
+            "vector".to_string(),
+        ]))
         .execute()
         .await?
         .try_collect::<Vec<_>>()
         .await?;
 
     for batch in results {
-        if let Some(mtime_array) = batch
-            .column_by_name("mtime")
-            .and_then(|c| c.as_any().downcast_ref::<Int64Array>())
-        {
-            if batch.num_rows() > 0 {
-                return Ok(Some(mtime_array.value(0)));
+        let hash_array = batch
+            .column_by_name("chunk_hash")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>());
+        let vector_array = batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>());
+
+        if let (Some(hashes), Some(vectors)) = (hash_array, vector_array) {
+            for i in 0..batch.num_rows() {
+                if let Some(floats) = vector_values(vectors.value(i).as_ref()) {
+                    cached.entry(hashes.value(i).to_string()).or_insert(floats);
+                }
             }
         }
     }
-    Ok(None)
+
+    Ok(cached)
+}
+
+pub async fn get_single_file_fingerprint(
+    table: &Table,
+    file_path: &str,
+) -> Result<Option<FileFingerprint>> {
+    let results = table
+        .query()
+        .only_if(Filter::path_is(file_path).to_sql())
+        .select(fingerprint_columns())
+        .limit(1)
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut found = HashMap::new();
+    for batch in &results {
+        fingerprints_from_batch(batch, &mut found);
+    }
+    Ok(found.remove(file_path))
+}
+
+pub async fn get_indexed_fingerprints(table: &Table) -> Result<HashMap<String, FileFingerprint>> {
+    let results = table
+        .query()
+        .select(fingerprint_columns())
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    let mut fingerprints = HashMap::new();
+    for batch in &results {
+        fingerprints_from_batch(batch, &mut fingerprints);
+    }
+    Ok(fingerprints)
 }
 
 pub async fn get_indexed_mtimes(table: &Table) -> Result<HashMap<String, i64>> {
@@ -107,70 +398,225 @@ pub async fn get_indexed_mtimes(table: &Table) -> Result<HashMap<String, i64>> {
     Ok(mtimes)
 }
 
-pub async fn get_or_create_table(db: &Connection, table_name: &str, dim: usize) -> Result<Table> {
-    match db.open_table(table_name).execute().await {
-        Ok(table) => {
-            let schema = table.schema().await?;
-            let has_mtime = schema.field_with_name("mtime").is_ok();
-            if let Ok(field) = schema.field_with_name("vector") {
-                if let DataType::FixedSizeList(_, size) = field.data_type() {
-                    if *size == dim as i32 && has_mtime {
-                        return Ok(table);
-                    }
+/// Opens the table and migrates it to the current schema, or creates it.
+/// A table that can't be migrated (other vector dimension or model) is left untouched
+/// and reported as [`RebuildRequired`], dropping it is the user's call.
+pub async fn get_or_create_table(
+    db: &Connection,
+    table_name: &str,
+    dim: usize,
+    model: &ModelIdentity,
+    vectors: &VectorConfig,
+) -> Result<Table> {
+    if let Ok(table) = db.open_table(table_name).execute().await {
+        let schema = table.schema().await?;
+        let stored_dim = match schema.field_with_name("vector").map(|f| f.data_type()) {
+            Ok(DataType::FixedSizeList(_, size)) => Some(*size),
+            _ => None,
+        };
+        if stored_dim != Some(dim as i32) {
+            let reason = match stored_dim {
+                Some(size) => format!(
+                    "vectors have {} dimensions, the current model produces {}",
+                    size, dim
+                ),
+                None => "the table has no vector column".to_string(),
+            };
+            return Err(RebuildRequired {
+                table_name: table_name.to_string(),
+                reason,
+            }
+            .into());
+        }
+        match stored_precision(&schema) {
+            Some(precision) if precision != vectors.precision => {
+                return Err(RebuildRequired {
+                    table_name: table_name.to_string(),
+                    reason: format!(
+                        "vectors are stored as {:?}, the container is set to {:?}",
+                        precision, vectors.precision
+                    ),
+                }
+                .into());
+            }
+            _ => {}
+        }
+        schema::migrate(db, &table, table_name).await?;
+        match schema::get_model(db, table_name).await? {
+            Some(indexed) if &indexed != model => {
+                return Err(RebuildRequired {
+                    table_name: table_name.to_string(),
+                    reason: format!(
+                        "it was built with {}, the current model is {}",
+                        indexed.describe(),
+                        model.describe()
+                    ),
                 }
+                .into());
             }
-            let _ = db.drop_table(table_name, &[]).await;
+            Some(_) => {}
+            // Built before models were recorded; the dimension matched, assume the model does
+            None => schema::set_model(db, table_name, model).await?,
         }
-        Err(_) => {}
+        return Ok(table);
     }
 
-    let schema = Arc::new(make_schema(dim));
+    let schema = Arc::new(make_schema(dim, vectors.precision));
 
     let table = db
         .create_table(table_name, RecordBatchIterator::new(vec![], schema))
         .execute()
         .await?;
+    schema::clear_meta(db, table_name).await?;
+    schema::set_version(db, table_name, schema::SCHEMA_VERSION).await?;
+    schema::set_model(db, table_name, model).await?;
 
     Ok(table)
 }
 
-fn make_schema(dim: usize) -> Schema {
+/// Columns of [`ChunkMeta`], for queries that return hits.
+pub const META_COLUMNS: &[&str] = &[
+    "chunk_index",
+    "start_byte",
+    "end_byte",
+    "start_line",
+    "end_line",
+    "file_size",
+    "extension",
+    "mtime",
+    "indexed_at",
+];
+
+/// Reads the [`ChunkMeta`] of every row of `batch`. Columns left out of the query read as defaults.
+pub fn chunk_metas(batch: &RecordBatch) -> Vec<ChunkMeta> {
+    fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Option<&'a T> {
+        batch
+            .column_by_name(name)
+            .and_then(|c| c.as_any().downcast_ref::<T>())
+    }
+    let ints = |name| column::<Int32Array>(batch, name);
+    let longs = |name| column::<Int64Array>(batch, name);
+
+    let chunk_index = ints("chunk_index");
+    let start_byte = longs("start_byte");
+    let end_byte = longs("end_byte");
+    let start_line = ints("start_line");
+    let end_line = ints("end_line");
+    let file_size = longs("file_size");
+    let extension = column::<StringArray>(batch, "extension");
+    let mtime = longs("mtime");
+    let indexed_at = longs("indexed_at");
+
+    (0..batch.num_rows())
+        .map(|i| ChunkMeta {
+            chunk_index: chunk_index.map_or(0, |a| a.value(i)),
+            start_byte: start_byte.map_or(0, |a| a.value(i)),
+            end_byte: end_byte.map_or(0, |a| a.value(i)),
+            start_line: start_line.map_or(0, |a| a.value(i)),
+            end_line: end_line.map_or(0, |a| a.value(i)),
+            file_size: file_size.map_or(0, |a| a.value(i)),
+            extension: extension.map_or_else(String::new, |a| a.value(i).to_string()),
+            mtime: mtime.map_or(0, |a| a.value(i)),
+            indexed_at: indexed_at.map_or(0, |a| a.value(i)),
+        })
+        .collect()
+}
+
+fn element_type(precision: VectorPrecision) -> DataType {
+    match precision {
+        VectorPrecision::Float32 => DataType::Float32,
+        VectorPrecision::Float16 => DataType::Float16,
+    }
+}
+
+/// Precision of the `vector` column, `None` when it has none or of another type.
+pub fn stored_precision(schema: &Schema) -> Option<VectorPrecision> {
+    match schema.field_with_name("vector").ok()?.data_type() {
+        DataType::FixedSizeList(item, _) => match item.data_type() {
+            DataType::Float32 => Some(VectorPrecision::Float32),
+            DataType::Float16 => Some(VectorPrecision::Float16),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+async fn table_precision(table: &Table) -> Result<VectorPrecision> {
+    stored_precision(&table.schema().await?).ok_or_else(|| anyhow!("Table has no vector column"))
+}
+
+/// One stored vector as `f32`, whichever precision it was written with.
+pub fn vector_values(values: &dyn Array) -> Option<Vec<f32>> {
+    if let Some(floats) = values.as_any().downcast_ref::<Float32Array>() {
+        return Some(floats.values().to_vec());
+    }
+    let halves = values.as_any().downcast_ref::<Float16Array>()?;
+    Some(halves.values().iter().map(|h| h.to_f32()).collect())
+}
+
+pub(crate) fn make_schema(dim: usize, precision: VectorPrecision) -> Schema {
     Schema::new(vec![
         Field::new("path", DataType::Utf8, false),
+        Field::new("chunk_index", DataType::Int32, false),
+        Field::new("chunk_hash", DataType::Utf8, false),
         Field::new("content", DataType::Utf8, false),
         Field::new(
             "vector",
             DataType::FixedSizeList(
-                Arc::new(Field::new("item", DataType::Float32, true)),
+                Arc::new(Field::new("item", element_type(precision), true)),
                 dim as i32,
             ),
             false,
         ),
         Field::new("mtime", DataType::Int64, false),
+        Field::new("content_hash", DataType::Utf8, false),
+        Field::new("start_byte", DataType::Int64, false),
+        Field::new("end_byte", DataType::Int64, false),
+        Field::new("start_line", DataType::Int32, false),
+        Field::new("end_line", DataType::Int32, false),
+        Field::new("file_size", DataType::Int64, false),
+        Field::new("extension", DataType::Utf8, false),
+        Field::new("indexed_at", DataType::Int64, false),
     ])
 }
 
-pub fn create_record_batch(records: Vec<Record>) -> Result<RecordBatch> {
+pub fn create_record_batch(records: Vec<Record>, precision: VectorPrecision) -> Result<RecordBatch> {
     if records.is_empty() {
         return Err(anyhow!("No records to convert"));
     }
 
     let dim = records[0].vector.len();
-    let schema = Arc::new(make_schema(dim));
+    let schema = Arc::new(make_schema(dim, precision));
 
     let paths: Vec<String> = records.iter().map(|r| r.path.clone()).collect();
+    let chunk_indices: Vec<i32> = records.iter().map(|r| r.meta.chunk_index).collect();
+    let chunk_hashes: Vec<String> = records.iter().map(|r| r.chunk_hash.clone()).collect();
     let contents: Vec<String> = records.iter().map(|r| r.content.clone()).collect();
-    let mtimes: Vec<i64> = records.iter().map(|r| r.mtime).collect();
+    let mtimes: Vec<i64> = records.iter().map(|r| r.meta.mtime).collect();
+    let hashes: Vec<String> = records.iter().map(|r| r.content_hash.clone()).collect();
+    let start_bytes: Vec<i64> = records.iter().map(|r| r.meta.start_byte).collect();
+    let end_bytes: Vec<i64> = records.iter().map(|r| r.meta.end_byte).collect();
+    let start_lines: Vec<i32> = records.iter().map(|r| r.meta.start_line).collect();
+    let end_lines: Vec<i32> = records.iter().map(|r| r.meta.end_line).collect();
+    let file_sizes: Vec<i64> = records.iter().map(|r| r.meta.file_size).collect();
+    let extensions: Vec<String> = records.iter().map(|r| r.meta.extension.clone()).collect();
+    let indexed_ats: Vec<i64> = records.iter().map(|r| r.meta.indexed_at).collect();
 
     let mut flat_vectors = Vec::with_capacity(records.len() * dim);
     for r in &records {
         flat_vectors.extend_from_slice(&r.vector);
     }
 
+    let values: Arc<dyn Array> = match precision {
+        VectorPrecision::Float32 => Arc::new(Float32Array::from(flat_vectors)),
+        VectorPrecision::Float16 => Arc::new(Float16Array::from(
+            flat_vectors.into_iter().map(half::f16::from_f32).collect::<Vec<_>>(),
+        )),
+    };
     let vector_array = FixedSizeListArray::try_new(
-        Arc::new(Field::new("item", DataType::Float32, true)),
+        Arc::new(Field::new("item", element_type(precision), true)),
         dim as i32,
-        Arc::new(Float32Array::from(flat_vectors)),
+        values,
         None,
     )?;
 
@@ -178,10 +624,75 @@ pub fn create_record_batch(records: Vec<Record>) -> Result<RecordBatch> {
         schema,
         vec![
             Arc::new(StringArray::from(paths)),
+            Arc::new(Int32Array::from(chunk_indices)),
+            Arc::new(StringArray::from(chunk_hashes)),
             Arc::new(StringArray::from(contents)),
             Arc::new(vector_array),
             Arc::new(Int64Array::from(mtimes)),
+            Arc::new(StringArray::from(hashes)),
+            Arc::new(Int64Array::from(start_bytes)),
+            Arc::new(Int64Array::from(end_bytes)),
+            Arc::new(Int32Array::from(start_lines)),
+            Arc::new(Int32Array::from(end_lines)),
+            Arc::new(Int64Array::from(file_sizes)),
+            Arc::new(StringArray::from(extensions)),
+            Arc::new(Int64Array::from(indexed_ats)),
         ],
     )
     .map_err(|e| anyhow!(e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fp(mtime: i64, hash: &str) -> FileFingerprint {
+        FileFingerprint {
+            mtime,
+            file_size: 10,
+            content_hash: hash.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint_touched_file_is_unchanged() {
+        assert!(fp(100, "abc").is_unchanged(200, "abc"));
+    }
+
+    #[test]
+    fn test_fingerprint_edit_with_preserved_mtime_is_changed() {
+        assert!(!fp(100, "abc").is_unchanged(100, "def"));
+    }
+
+    #[test]
+    fn test_float16_batch_round_trips() {
+        let record = Record {
+            path: "@a1/x.rs".to_string(),
+            chunk_hash: "h".to_string(),
+            content: "fn x() {}".to_string(),
+            vector: vec![0.5, -0.25, 1.0],
+            content_hash: "c".to_string(),
+            meta: ChunkMeta::default(),
+        };
+        let batch = create_record_batch(vec![record], VectorPrecision::Float16).unwrap();
+        assert_eq!(stored_precision(&batch.schema()), Some(VectorPrecision::Float16));
+        let vectors = batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>())
+            .unwrap();
+        assert_eq!(vector_values(vectors.value(0).as_ref()), Some(vec![0.5, -0.25, 1.0]));
+    }
+
+    #[test]
+    fn test_fingerprint_same_stat() {
+        assert!(fp(100, "abc").same_stat(100, 10));
+        assert!(!fp(100, "abc").same_stat(100, 11));
+        assert!(!fp(100, "abc").same_stat(101, 10));
+    }
+
+    #[test]
+    fn test_fingerprint_without_hash_falls_back_to_mtime() {
+        assert!(fp(100, "").is_unchanged(100, "def"));
+        assert!(!fp(100, "").is_unchanged(101, "def"));
+    }
+}
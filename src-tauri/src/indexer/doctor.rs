@@ -0,0 +1,381 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use anyhow::Result;
+use arrow_array::{Array, FixedSizeListArray, StringArray};
+use arrow_schema::DataType;
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use lancedb::Table;
+use serde::Serialize;
+
+use super::maintenance::{self, ANN_INDEX_THRESHOLD};
+use super::paths::PathMap;
+use super::reembed::shadow_table_name;
+use super::{db, schema};
+use crate::config::{get_table_name, ContainerInfo};
+use crate::jobs::JobManager;
+
+/// Files listed in [`Issue::describe`] before the rest is only counted.
+const LISTED_FILES: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexKind {
+    Vector,
+    FullText,
+    ChunkHash,
+}
+
+/// Something [`check`] found wrong with the database. File paths are stored paths.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Issue {
+    /// A container table no container owns: a delete that failed halfway, or the shadow
+    /// of a re-embed that never finished.
+    OrphanTable { table: String },
+    /// A container with folders but no table.
+    MissingTable { container: String },
+    /// Rows stored under none of the container's folders, e.g. after a folder was taken
+    /// out of the config by hand.
+    OutsideRoots { container: String, files: Vec<String> },
+    /// Vectors of another size than the current model produces.
+    WrongDimension {
+        container: String,
+        stored: Option<usize>,
+        expected: usize,
+    },
+    /// Files with a vector holding NaN or infinity. Such a chunk never matches a search.
+    CorruptVectors { container: String, files: Vec<String> },
+    MissingIndex { container: String, index: IndexKind },
+}
+
+impl Issue {
+    pub fn describe(&self) -> String {
+        match self {
+            Issue::OrphanTable { table } => format!("table {} belongs to no container", table),
+            Issue::MissingTable { container } => {
+                format!("'{}' has folders but no index", container)
+            }
+            Issue::OutsideRoots { container, files } => format!(
+                "'{}': {} outside every indexed folder",
+                container,
+                list_files(files)
+            ),
+            Issue::WrongDimension { container, stored, expected } => match stored {
+                Some(size) => format!(
+                    "'{}': vectors have {} dimensions, the current model produces {}",
+                    container, size, expected
+                ),
+                None => format!("'{}': the table has no vector column", container),
+            },
+            Issue::CorruptVectors { container, files } => {
+                format!("'{}': NaN or infinite vectors in {}", container, list_files(files))
+            }
+            Issue::MissingIndex { container, index } => {
+                let name = match index {
+                    IndexKind::Vector => "vector",
+                    IndexKind::FullText => "full-text",
+                    IndexKind::ChunkHash => "chunk hash",
+                };
+                format!("'{}': no {} index", container, name)
+            }
+        }
+    }
+
+    /// Whether [`repair`] fixes it. A missing table needs indexing and a wrong dimension
+    /// a rebuild, both left to the user.
+    pub fn repairable(&self) -> bool {
+        !matches!(self, Issue::MissingTable { .. } | Issue::WrongDimension { .. })
+    }
+}
+
+fn list_files(files: &[String]) -> String {
+    let mut listed = files[..files.len().min(LISTED_FILES)].join(", ");
+    if files.len() > LISTED_FILES {
+        listed.push_str(&format!(" and {} more", files.len() - LISTED_FILES));
+    }
+    listed
+}
+
+/// Tables that look like a container's or a shadow's but that no container owns.
+/// `busy` containers are being written to; their shadow is in use. Other tables,
+/// like the schema metadata, are never reported.
+fn orphan_tables(
+    table_names: &[String],
+    containers: &HashSet<String>,
+    busy: &HashSet<String>,
+) -> Vec<String> {
+    let live: HashSet<String> = containers.iter().map(|c| get_table_name(c)).collect();
+    let shadows: HashSet<String> = busy
+        .iter()
+        .map(|c| shadow_table_name(&get_table_name(c)))
+        .collect();
+    table_names
+        .iter()
+        .filter(|name| {
+            let owned = live.contains(*name) || shadows.contains(*name);
+            let managed = name.starts_with("c_") || name.starts_with(&shadow_table_name("c_"));
+            managed && !owned
+        })
+        .cloned()
+        .collect()
+}
+
+/// Whether a stored path lies under none of the roots of `paths`, once resolved.
+fn outside_roots(stored: &str, paths: &PathMap) -> bool {
+    paths.store(Path::new(&paths.resolve(stored))).is_none()
+}
+
+/// Looks for problems across the whole database without changing anything.
+/// Containers in `busy` are skipped: a running job would make them look broken.
+pub async fn check(
+    db: &Connection,
+    containers: &HashMap<String, ContainerInfo>,
+    dim: usize,
+    busy: &HashSet<String>,
+) -> Result<Vec<Issue>> {
+    let table_names = db.table_names().execute().await?;
+    let names: HashSet<String> = containers.keys().cloned().collect();
+    let mut issues: Vec<Issue> = orphan_tables(&table_names, &names, busy)
+        .into_iter()
+        .map(|table| Issue::OrphanTable { table })
+        .collect();
+
+    let mut sorted: Vec<(&String, &ContainerInfo)> = containers.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(b.0));
+    for (container, info) in sorted {
+        if busy.contains(container) {
+            continue;
+        }
+        let Ok(table) = db.open_table(get_table_name(container)).execute().await else {
+            if !info.indexed_paths.is_empty() {
+                issues.push(Issue::MissingTable { container: container.clone() });
+            }
+            continue;
+        };
+        let paths = PathMap::new(info.indexed_paths.clone());
+        issues.extend(check_table(&table, container, &paths, dim).await?);
+    }
+    Ok(issues)
+}
+
+async fn check_table(
+    table: &Table,
+    container: &str,
+    paths: &PathMap,
+    dim: usize,
+) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+
+    let stored = match table.schema().await?.field_with_name("vector").map(|f| f.data_type()) {
+        Ok(DataType::FixedSizeList(_, size)) => Some(*size as usize),
+        _ => None,
+    };
+    if stored != Some(dim) {
+        issues.push(Issue::WrongDimension {
+            container: container.to_string(),
+            stored,
+            expected: dim,
+        });
+    }
+
+    // Streamed: a large table's vectors don't fit in memory at once
+    let mut outside: HashSet<String> = HashSet::new();
+    let mut corrupt: HashSet<String> = HashSet::new();
+    let columns = if stored.is_some() { vec!["path", "vector"] } else { vec!["path"] };
+    let mut batches = table
+        .query()
+        .select(Select::Columns(columns.iter().map(|c| c.to_string()).collect()))
+        .execute()
+        .await?;
+    while let Some(batch) = batches.try_next().await? {
+        let Some(stored_paths) = batch
+            .column_by_name("path")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        else {
+            continue;
+        };
+        let vectors = batch
+            .column_by_name("vector")
+            .and_then(|c| c.as_any().downcast_ref::<FixedSizeListArray>());
+        for i in 0..batch.num_rows() {
+            let path = stored_paths.value(i);
+            if outside_roots(path, paths) {
+                outside.insert(path.to_string());
+            }
+            let broken = vectors
+                .and_then(|v| db::vector_values(v.value(i).as_ref()))
+                .is_some_and(|values| values.iter().any(|x| !x.is_finite()));
+            if broken {
+                corrupt.insert(path.to_string());
+            }
+        }
+    }
+    if !outside.is_empty() {
+        let mut files: Vec<String> = outside.into_iter().collect();
+        files.sort();
+        issues.push(Issue::OutsideRoots { container: container.to_string(), files });
+    }
+    if !corrupt.is_empty() {
+        let mut files: Vec<String> = corrupt.into_iter().collect();
+        files.sort();
+        issues.push(Issue::CorruptVectors { container: container.to_string(), files });
+    }
+
+    let health = maintenance::table_health(table).await?;
+    let missing = [
+        (health.vector.is_none() && health.rows >= ANN_INDEX_THRESHOLD, IndexKind::Vector),
+        (health.fts.is_none() && health.rows > 0, IndexKind::FullText),
+        (health.chunk_hash.is_none() && health.rows > 0, IndexKind::ChunkHash),
+    ];
+    for (is_missing, index) in missing {
+        if is_missing {
+            issues.push(Issue::MissingIndex { container: container.to_string(), index });
+        }
+    }
+    Ok(issues)
+}
+
+/// Fixes the [`Issue::repairable`] ones among `issues`: drops orphan tables, deletes the
+/// rows of files outside every folder or with broken vectors, and builds missing indexes.
+/// A deleted file comes back at the next indexing run if it still belongs there.
+/// `issues` may be stale, so jobs are looked at again right before each change: a table
+/// a job has taken up since is kept, and a container is reserved while it is fixed.
+/// Returns the issues fixed; those skipped for a job are not.
+pub async fn repair(
+    db: &Connection,
+    containers: &HashMap<String, ContainerInfo>,
+    issues: &[Issue],
+    jobs: &JobManager,
+) -> Result<Vec<Issue>> {
+    const DOING: &str = "repairing the index";
+    let mut repaired = Vec::new();
+    let mut refreshed: HashSet<&str> = HashSet::new();
+    for issue in issues {
+        match issue {
+            Issue::OrphanTable { table } => {
+                let owned = containers.keys().any(|c| get_table_name(c) == *table);
+                let in_use = jobs.list().await.iter().any(|job| {
+                    let live = get_table_name(&job.container);
+                    *table == live || *table == shadow_table_name(&live)
+                });
+                if owned || in_use {
+                    continue;
+                }
+                db.drop_table(table, &[]).await?;
+                schema::clear_meta(db, table).await?;
+            }
+            Issue::OutsideRoots { container, files } | Issue::CorruptVectors { container, files } => {
+                let Ok(_reservation) = jobs.reserve(container, DOING).await else {
+                    continue;
+                };
+                let table = db.open_table(get_table_name(container)).execute().await?;
+                db::delete_paths(&table, files).await?;
+            }
+            Issue::MissingIndex { container, .. } => {
+                // One refresh builds every missing index of the table
+                if !refreshed.contains(container.as_str()) {
+                    let Ok(_reservation) = jobs.reserve(container, DOING).await else {
+                        continue;
+                    };
+                    let vectors = containers
+                        .get(container)
+                        .map(|info| info.vectors.clone())
+                        .unwrap_or_default();
+                    let table = db.open_table(get_table_name(container)).execute().await?;
+                    maintenance::refresh_indexes(&table, &vectors).await?;
+                    refreshed.insert(container.as_str());
+                }
+            }
+            Issue::MissingTable { .. } | Issue::WrongDimension { .. } => continue,
+        }
+        repaired.push(issue.clone());
+    }
+    Ok(repaired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::IndexedRoot;
+
+    fn set(items: &[&str]) -> HashSet<String> {
+        items.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_orphan_tables() {
+        let tables: Vec<String> = ["c_Default", "c_Old", "shadow_c_Work", "shadow_c_Gone", "recall_meta"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let orphans = orphan_tables(&tables, &set(&["Default", "Work"]), &set(&["Work"]));
+        assert_eq!(orphans, vec!["c_Old".to_string(), "shadow_c_Gone".to_string()]);
+
+        // Without a job running, a leftover shadow is an orphan too
+        let orphans = orphan_tables(&tables, &set(&["Default", "Work"]), &HashSet::new());
+        assert!(orphans.contains(&"shadow_c_Work".to_string()));
+    }
+
+    #[test]
+    fn test_outside_roots() {
+        let paths = PathMap::new(vec![IndexedRoot {
+            id: "a1".to_string(),
+            path: "/code".to_string(),
+        }]);
+        assert!(!outside_roots("@a1/src/main.rs", &paths));
+        assert!(!outside_roots("/code/legacy.rs", &paths));
+        assert!(outside_roots("@gone/x.rs", &paths));
+        assert!(outside_roots("/elsewhere/x.rs", &paths));
+    }
+
+    #[tokio::test]
+    async fn test_repair_skips_tables_a_job_took_up() {
+        let dir = std::env::temp_dir().join("recall_lite_doctor_repair");
+        let _ = std::fs::remove_dir_all(&dir);
+        let db = lancedb::connect(dir.join("db").to_str().unwrap())
+            .execute()
+            .await
+            .unwrap();
+        let schema = std::sync::Arc::new(db::make_schema(4, Default::default()));
+        for name in ["shadow_c_Work", "c_Old"] {
+            let empty = arrow_array::RecordBatchIterator::new(vec![], schema.clone());
+            db.create_table(name, empty).execute().await.unwrap();
+        }
+        let (tx, _rx) = crate::events::channel();
+        let jobs = JobManager::new(tx, dir.join("jobs"));
+
+        // Checked while nothing ran; a re-embed of Work started before the user confirmed
+        let issues = vec![
+            Issue::OrphanTable { table: "shadow_c_Work".to_string() },
+            Issue::OrphanTable { table: "c_Old".to_string() },
+            Issue::OutsideRoots {
+                container: "Work".to_string(),
+                files: vec!["@gone/x.rs".to_string()],
+            },
+        ];
+        jobs.start(crate::jobs::JobKind::ReembedModel, "Work", &[])
+            .await
+            .unwrap();
+        let repaired = repair(&db, &HashMap::new(), &issues, &jobs).await.unwrap();
+        assert_eq!(repaired, vec![issues[1].clone()]);
+        let tables = db.table_names().execute().await.unwrap();
+        assert_eq!(tables, vec!["shadow_c_Work".to_string()]);
+    }
+
+    #[test]
+    fn test_describe_lists_a_few_files() {
+        let issue = Issue::CorruptVectors {
+            container: "Work".to_string(),
+            files: (1..=5).map(|n| format!("@a1/{}.md", n)).collect(),
+        };
+        assert_eq!(
+            issue.describe(),
+            "'Work': NaN or infinite vectors in @a1/1.md, @a1/2.md, @a1/3.md and 2 more"
+        );
+        assert!(issue.repairable());
+        assert!(!Issue::MissingTable { container: "Work".to_string() }.repairable());
+    }
+}
@@ -3,10 +3,49 @@ use std::panic::AssertUnwindSafe;
 use anyhow::{anyhow, Result};
 use fastembed::{EmbeddingModel, InitOptions, TextEmbedding};
 use fastembed::{RerankInitOptions, RerankResult, RerankerModel, TextRerank};
+use serde::{Deserialize, Serialize};
+
+use super::search::SearchHit;
 
 const QUERY_PREFIX: &str = "query: ";
 const PASSAGE_PREFIX: &str = "passage: ";
 
+/// What a table's vectors were computed with. Two models of the same dimension
+/// give vectors that fit the same column but don't compare.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelIdentity {
+    /// Config name, e.g. `MultilingualE5Base`.
+    pub name: String,
+    /// Upstream repository and ONNX file, as pinned by fastembed.
+    pub revision: String,
+    pub passage_prefix: String,
+    pub query_prefix: String,
+}
+
+impl ModelIdentity {
+    /// Identity of the model `Config.embedding_model` resolves to.
+    pub fn new(config_name: &str) -> Self {
+        let model = crate::config::get_embedding_model(config_name);
+        let revision = TextEmbedding::get_model_info(&model)
+            .map(|info| format!("{}/{}", info.model_code, info.model_file))
+            .unwrap_or_default();
+        Self {
+            name: format!("{:?}", model),
+            revision,
+            passage_prefix: PASSAGE_PREFIX.to_string(),
+            query_prefix: QUERY_PREFIX.to_string(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        if self.revision.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{} ({})", self.name, self.revision)
+        }
+    }
+}
+
 pub fn load_model(model: EmbeddingModel, cache_dir: std::path::PathBuf) -> Result<TextEmbedding> {
     let mut options = InitOptions::default();
     options.model_name = model;
@@ -34,7 +73,11 @@ pub fn embed_passages(model: &mut TextEmbedding, texts: Vec<String>) -> Result<V
 }
 
 pub fn embed_query(model: &mut TextEmbedding, query: &str) -> Result<Vec<f32>> {
-    let prefixed = format!("{}{}", QUERY_PREFIX, query);
+    embed_prefixed_query(model, QUERY_PREFIX, query)
+}
+
+fn embed_prefixed_query(model: &mut TextEmbedding, prefix: &str, query: &str) -> Result<Vec<f32>> {
+    let prefixed = format!("{}{}", prefix, query);
     let embeddings = model
         .embed(vec![prefixed], None)
         .map_err(|e| anyhow!("Embedding failed: {}", e))?;
@@ -44,6 +87,27 @@ pub fn embed_query(model: &mut TextEmbedding, query: &str) -> Result<Vec<f32>> {
         .ok_or_else(|| anyhow!("Empty embedding result"))
 }
 
+/// The model a table was built with before the config switched to another one, kept
+/// loaded while the table is re-embedded so it stays searchable.
+pub struct LegacyModel {
+    pub identity: ModelIdentity,
+    model: TextEmbedding,
+}
+
+impl LegacyModel {
+    pub fn load(identity: ModelIdentity, cache_dir: std::path::PathBuf) -> Result<Self> {
+        if ModelIdentity::new(&identity.name) != identity {
+            return Err(anyhow!("{} is not available in this build", identity.describe()));
+        }
+        let model = load_model(crate::config::get_embedding_model(&identity.name), cache_dir)?;
+        Ok(Self { identity, model })
+    }
+
+    pub fn embed_query(&mut self, query: &str) -> Result<Vec<f32>> {
+        embed_prefixed_query(&mut self.model, &self.identity.query_prefix, query)
+    }
+}
+
 pub fn get_model_dimension(model: &mut TextEmbedding) -> Result<usize> {
     let probe = model
         .embed(vec!["dimension probe".to_string()], None)
@@ -70,17 +134,15 @@ fn truncate_to_byte_boundary(s: &str, max_bytes: usize) -> &str {
 pub fn rerank_results(
     reranker: &mut TextRerank,
     query: &str,
-    results: &[(String, String, f32)],
-) -> Result<Vec<(String, String, f32)>> {
+    results: &[SearchHit],
+) -> Result<Vec<SearchHit>> {
     if results.is_empty() {
         return Ok(vec![]);
     }
 
     let truncated: Vec<String> = results
         .iter()
-        .map(|(_, snippet, _)| {
-            truncate_to_byte_boundary(snippet, RERANK_MAX_SNIPPET_BYTES).to_string()
-        })
+        .map(|hit| truncate_to_byte_boundary(&hit.content, RERANK_MAX_SNIPPET_BYTES).to_string())
         .collect();
     let doc_refs: Vec<&str> = truncated.iter().map(|s| s.as_str()).collect();
     let reranked = reranker
@@ -89,9 +151,9 @@ pub fn rerank_results(
 
     Ok(reranked
         .into_iter()
-        .map(|RerankResult { index, score, .. }| {
-            let (path, snippet, _) = &results[index];
-            (path.clone(), snippet.clone(), score)
+        .map(|RerankResult { index, score, .. }| SearchHit {
+            score,
+            ..results[index].clone()
         })
         .collect())
 }
@@ -99,8 +161,8 @@ pub fn rerank_results(
 pub async fn safe_rerank(
     reranker: fastembed::TextRerank,
     query: String,
-    input: Vec<(String, String, f32)>,
-) -> (Option<fastembed::TextRerank>, Vec<(String, String, f32)>, bool) {
+    input: Vec<SearchHit>,
+) -> (Option<fastembed::TextRerank>, Vec<SearchHit>, bool) {
     let fallback = input.clone();
     match tokio::task::spawn_blocking(move || {
         let mut r = reranker;
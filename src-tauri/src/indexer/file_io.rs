@@ -181,13 +181,29 @@ pub fn read_file_content(path: &Path) -> Option<String> {
     }
 }
 
-pub fn read_file_content_with_config(path: &Path, config: &IndexingConfig) -> Option<String> {
-    if let Ok(meta) = fs::metadata(path) {
-        if meta.len() > MAX_FILE_SIZE {
-            return None;
-        }
-    }
+/// Why a file produced no text to index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReadError {
+    /// Not a text type, PDF or image: nothing to extract, not worth reporting per file.
+    Unsupported,
+    /// Left out on purpose (size limit, excluded extension, binary content...).
+    Skipped(String),
+    /// Reading or parsing went wrong.
+    Failed(String),
+}
+
+/// How a file's text is extracted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reader {
+    Text,
+    Pdf,
+    Image,
+}
 
+/// How `path` is read, or why it isn't, judged from its name and the config alone.
+/// The one place that decides it: checked before hashing so binaries cost nothing,
+/// and again by [`try_read_file_content`].
+pub fn reader_for(path: &Path, config: &IndexingConfig) -> Result<Reader, ReadError> {
     let ext = path
         .extension()
         .and_then(|s| s.to_str())
@@ -206,18 +222,57 @@ pub fn read_file_content_with_config(path: &Path, config: &IndexingConfig) -> Op
     );
 
     if config.excluded_extensions.iter().any(|e| e == &ext) {
-        return None;
+        return Err(ReadError::Skipped("extension excluded in config".to_string()));
     }
-
     if is_text_extension_with_config(&ext, config) || is_dotfile {
-        fs::read_to_string(path).ok()
+        Ok(Reader::Text)
     } else if ext == "pdf" {
-        pdf_extract::extract_text(path).ok()
+        Ok(Reader::Pdf)
+    } else if super::ocr::is_image_extension(&ext) {
+        Ok(Reader::Image)
     } else {
-        None
+        Err(ReadError::Unsupported)
+    }
+}
+
+fn too_large() -> ReadError {
+    ReadError::Skipped(format!("larger than {} MB", MAX_FILE_SIZE / (1024 * 1024)))
+}
+
+fn io_failed(e: std::io::Error) -> ReadError {
+    ReadError::Failed(e.to_string())
+}
+
+pub fn read_file_content_with_config(path: &Path, config: &IndexingConfig) -> Option<String> {
+    try_read_file_content(path, config).ok()
+}
+
+/// Like [`read_file_content_with_config`], but says why nothing came out. Images are
+/// [`ReadError::Unsupported`] here, see [`try_read_image_text`].
+pub fn try_read_file_content(path: &Path, config: &IndexingConfig) -> Result<String, ReadError> {
+    let reader = reader_for(path, config)?;
+    if fs::metadata(path).map_err(io_failed)?.len() > MAX_FILE_SIZE {
+        return Err(too_large());
+    }
+
+    match reader {
+        Reader::Text => {
+            let bytes = fs::read(path).map_err(io_failed)?;
+            String::from_utf8(bytes).map_err(|_| ReadError::Skipped("not valid UTF-8".to_string()))
+        }
+        Reader::Pdf => {
+            pdf_extract::extract_text(path).map_err(|e| ReadError::Failed(format!("PDF: {}", e)))
+        }
+        Reader::Image => Err(ReadError::Unsupported),
     }
 }
 
+pub async fn try_read_image_text(path: &Path) -> Result<String, ReadError> {
+    super::ocr::extract_text_from_image(path)
+        .await
+        .map_err(|e| ReadError::Failed(format!("OCR: {}", e)))
+}
+
 pub async fn read_file_content_with_ocr(path: &Path) -> Option<String> {
     let ext = path
         .extension()
@@ -232,6 +287,19 @@ pub async fn read_file_content_with_ocr(path: &Path) -> Option<String> {
     }
 }
 
+/// BLAKE3 digest of the raw file bytes, hex-encoded.
+pub fn hash_file(path: &Path) -> Result<String, ReadError> {
+    if fs::metadata(path).map_err(io_failed)?.len() > MAX_FILE_SIZE {
+        return Err(too_large());
+    }
+    let bytes = fs::read(path).map_err(io_failed)?;
+    Ok(blake3::hash(&bytes).to_hex().to_string())
+}
+
+pub fn get_file_size(path: &Path) -> i64 {
+    fs::metadata(path).map(|m| m.len() as i64).unwrap_or(0)
+}
+
 pub fn get_file_mtime(path: &Path) -> i64 {
     fs::metadata(path)
         .and_then(|m| m.modified())
@@ -254,4 +322,39 @@ mod tests {
         assert!(!is_text_extension("exe"));
         assert!(!is_text_extension("png"));
     }
+
+    #[test]
+    fn test_try_read_reports_reason() {
+        let dir = std::env::temp_dir().join("recall_lite_file_io_test");
+        fs::create_dir_all(&dir).unwrap();
+        let config = IndexingConfig::default();
+
+        let binary = dir.join("latin1.txt");
+        fs::write(&binary, [0x63, 0x61, 0x66, 0xe9]).unwrap();
+        assert_eq!(
+            try_read_file_content(&binary, &config),
+            Err(ReadError::Skipped("not valid UTF-8".to_string()))
+        );
+
+        let unknown = dir.join("archive.xyz");
+        fs::write(&unknown, b"data").unwrap();
+        assert_eq!(try_read_file_content(&unknown, &config), Err(ReadError::Unsupported));
+
+        let excluded = IndexingConfig {
+            excluded_extensions: vec!["txt".to_string()],
+            ..IndexingConfig::default()
+        };
+        let skipped = Err(ReadError::Skipped("extension excluded in config".to_string()));
+        assert_eq!(reader_for(&binary, &excluded), skipped);
+        assert_eq!(try_read_file_content(&binary, &excluded), skipped);
+        assert_eq!(reader_for(&dir.join("scan.png"), &config), Ok(Reader::Image));
+
+        let missing = dir.join("missing.txt");
+        assert!(matches!(
+            try_read_file_content(&missing, &config),
+            Err(ReadError::Failed(_))
+        ));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,215 @@
+/// A literal compared against a column.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Str(String),
+    Int(i64),
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Self {
+        Value::Str(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Self {
+        Value::Str(s)
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Self {
+        Value::Int(n)
+    }
+}
+
+/// A predicate for LanceDB `only_if` / `delete`, rendered to SQL in one place so no
+/// caller escapes anything by hand. Column names are `&'static str`: they come from the
+/// table schema, never from input.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Eq(&'static str, Value),
+    /// Matches nothing when the list is empty.
+    In(&'static str, Vec<Value>),
+    StartsWith(&'static str, String),
+    EndsWith(&'static str, String),
+    /// Inclusive on both ends; `None` leaves that end open.
+    Between(&'static str, Option<i64>, Option<i64>),
+    /// Matches everything when empty.
+    And(Vec<Filter>),
+    /// Matches nothing when empty.
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn path_is(path: &str) -> Self {
+        Filter::Eq("path", path.into())
+    }
+
+    pub fn path_in(paths: &[String]) -> Self {
+        Filter::In("path", paths.iter().map(|p| p.as_str().into()).collect())
+    }
+
+    pub fn path_under(prefix: &str) -> Self {
+        Filter::StartsWith("path", prefix.to_string())
+    }
+
+    /// Paths under any of `prefixes`. Matches nothing when empty.
+    pub fn path_under_any(prefixes: &[String]) -> Self {
+        match prefixes {
+            [prefix] => Filter::path_under(prefix),
+            _ => Filter::Or(prefixes.iter().map(|p| Filter::path_under(p)).collect()),
+        }
+    }
+
+    /// Files ending in any of `extensions`, given with or without their dot.
+    pub fn extension_in(extensions: &[String]) -> Self {
+        Filter::Or(
+            extensions
+                .iter()
+                .map(|ext| Filter::EndsWith("path", format!(".{}", ext.trim_start_matches('.'))))
+                .collect(),
+        )
+    }
+
+    /// Files whose mtime, in Unix seconds, lies within the bounds.
+    pub fn modified_between(after: Option<i64>, before: Option<i64>) -> Self {
+        Filter::Between("mtime", after, before)
+    }
+
+    /// All of `filters`, or `None` when there are none.
+    pub fn all(filters: Vec<Filter>) -> Option<Self> {
+        match filters.len() {
+            0 => None,
+            1 => filters.into_iter().next(),
+            _ => Some(Filter::And(filters)),
+        }
+    }
+
+    pub fn to_sql(&self) -> String {
+        match self {
+            Filter::Eq(column, value) => format!("{} = {}", column, literal(value)),
+            Filter::In(_, values) if values.is_empty() => "FALSE".to_string(),
+            Filter::In(column, values) => {
+                let values: Vec<String> = values.iter().map(literal).collect();
+                format!("{} IN ({})", column, values.join(", "))
+            }
+            Filter::StartsWith(column, prefix) => {
+                format!("{} LIKE '{}%' ESCAPE '\\'", column, like_escape(prefix))
+            }
+            Filter::EndsWith(column, suffix) => {
+                format!("{} LIKE '%{}' ESCAPE '\\'", column, like_escape(suffix))
+            }
+            Filter::Between(_, None, None) => "TRUE".to_string(),
+            Filter::Between(column, Some(min), None) => format!("{} >= {}", column, min),
+            Filter::Between(column, None, Some(max)) => format!("{} <= {}", column, max),
+            Filter::Between(column, Some(min), Some(max)) => {
+                format!("{} BETWEEN {} AND {}", column, min, max)
+            }
+            Filter::And(filters) if filters.is_empty() => "TRUE".to_string(),
+            Filter::And(filters) => {
+                let parts: Vec<String> = filters.iter().map(Filter::operand).collect();
+                parts.join(" AND ")
+            }
+            Filter::Or(filters) if filters.is_empty() => "FALSE".to_string(),
+            Filter::Or(filters) => {
+                let parts: Vec<String> = filters.iter().map(Filter::operand).collect();
+                format!("({})", parts.join(" OR "))
+            }
+            Filter::Not(filter) => format!("NOT ({})", filter.to_sql()),
+        }
+    }
+
+    /// Rendered as the operand of AND / OR: a nested AND gets parentheses, the rest
+    /// already binds tighter or brings its own.
+    fn operand(&self) -> String {
+        match self {
+            Filter::And(filters) if filters.len() > 1 => format!("({})", self.to_sql()),
+            _ => self.to_sql(),
+        }
+    }
+}
+
+fn literal(value: &Value) -> String {
+    match value {
+        Value::Str(s) => format!("'{}'", s.replace('\'', "''")),
+        Value::Int(n) => n.to_string(),
+    }
+}
+
+/// Escapes a LIKE pattern fragment for use with `ESCAPE '\'`.
+fn like_escape(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('\'', "''")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quotes_are_doubled_in_literals() {
+        assert_eq!(Filter::path_is("/docs/it's.md").to_sql(), "path = '/docs/it''s.md'");
+    }
+
+    #[test]
+    fn test_backslashes_stay_literal_outside_like() {
+        assert_eq!(
+            Filter::path_is("C:\\Users\\me\\a_b.txt").to_sql(),
+            "path = 'C:\\Users\\me\\a_b.txt'"
+        );
+    }
+
+    #[test]
+    fn test_like_wildcards_are_escaped() {
+        assert_eq!(
+            Filter::path_under("C:\\100%_done'").to_sql(),
+            "path LIKE 'C:\\\\100\\%\\_done''%' ESCAPE '\\'"
+        );
+    }
+
+    #[test]
+    fn test_empty_lists() {
+        assert_eq!(Filter::path_in(&[]).to_sql(), "FALSE");
+        assert_eq!(Filter::extension_in(&[]).to_sql(), "FALSE");
+        assert_eq!(Filter::And(Vec::new()).to_sql(), "TRUE");
+        assert_eq!(Filter::all(Vec::new()), None);
+    }
+
+    #[test]
+    fn test_path_under_any() {
+        assert_eq!(
+            Filter::path_under_any(&["@a1/src".to_string()]).to_sql(),
+            "path LIKE '@a1/src%' ESCAPE '\\'"
+        );
+        assert_eq!(
+            Filter::path_under_any(&["@a1/src".to_string(), "@b2/src".to_string()]).to_sql(),
+            "(path LIKE '@a1/src%' ESCAPE '\\' OR path LIKE '@b2/src%' ESCAPE '\\')"
+        );
+    }
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(Filter::modified_between(Some(10), None).to_sql(), "mtime >= 10");
+        assert_eq!(Filter::modified_between(None, Some(20)).to_sql(), "mtime <= 20");
+        assert_eq!(
+            Filter::modified_between(Some(10), Some(20)).to_sql(),
+            "mtime BETWEEN 10 AND 20"
+        );
+    }
+
+    #[test]
+    fn test_nesting_keeps_precedence() {
+        let filter = Filter::Or(vec![
+            Filter::And(vec![Filter::path_is("a"), Filter::Eq("chunk_index", Value::Int(0))]),
+            Filter::Not(Box::new(Filter::path_under("b"))),
+        ]);
+        assert_eq!(
+            filter.to_sql(),
+            "((path = 'a' AND chunk_index = 0) OR NOT (path LIKE 'b%' ESCAPE '\\'))"
+        );
+    }
+}
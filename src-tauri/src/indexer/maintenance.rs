@@ -0,0 +1,328 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use lancedb::index::IndexType;
+use lancedb::table::{CompactionOptions, OptimizeAction, OptimizeOptions};
+use lancedb::Table;
+use serde::Serialize;
+use tokio::sync::Mutex;
+
+use super::db;
+use crate::config::{get_table_name, AnnIndexType, ConfigState, MaintenanceConfig, VectorConfig};
+use crate::jobs::JobManager;
+use crate::state::DbState;
+use crate::watcher;
+
+/// IVF-PQ needs enough rows to train its partitions; below this, brute force is fast anyway.
+pub const ANN_INDEX_THRESHOLD: usize = 256;
+
+/// Old table versions kept after compaction, so a reader mid-query never loses its files.
+const VERSION_RETENTION_HOURS: i64 = 1;
+
+/// Rows covered by an index vs. appended since it was last built or optimized.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct IndexCoverage {
+    pub indexed: usize,
+    pub unindexed: usize,
+}
+
+/// How far a table has drifted since its last maintenance.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct TableHealth {
+    pub rows: usize,
+    pub fragments: usize,
+    pub small_fragments: usize,
+    /// `None` when the index does not exist.
+    pub vector: Option<IndexCoverage>,
+    /// Type of the vector index, `None` when missing or not one this app builds.
+    pub vector_type: Option<AnnIndexType>,
+    pub fts: Option<IndexCoverage>,
+    pub chunk_hash: Option<IndexCoverage>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MaintenancePlan {
+    pub compact: bool,
+    /// Train the vector index from scratch: missing, or the table outgrew its partitions.
+    pub rebuild_vector: bool,
+    pub create_fts: bool,
+    pub create_chunk_hash: bool,
+    /// Fold unindexed rows into the existing indexes.
+    pub optimize_indices: bool,
+}
+
+impl MaintenancePlan {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn summary(&self) -> String {
+        let steps = [
+            (self.compact, "compacted"),
+            (self.rebuild_vector, "rebuilt vector index"),
+            (self.create_fts, "built search index"),
+            (self.create_chunk_hash, "built chunk hash index"),
+            (self.optimize_indices, "optimized indexes"),
+        ];
+        steps
+            .iter()
+            .filter(|(run, _)| *run)
+            .map(|(_, label)| *label)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Whether the vector index exists but isn't the type the container asks for.
+/// `Auto` accepts whatever is there.
+pub fn vector_index_outdated(health: &TableHealth, wanted: AnnIndexType) -> bool {
+    health.vector.is_some() && wanted != AnnIndexType::Auto && health.vector_type != Some(wanted)
+}
+
+fn ann_index_type(index_type: &IndexType) -> Option<AnnIndexType> {
+    match index_type {
+        IndexType::IvfFlat => Some(AnnIndexType::IvfFlat),
+        IndexType::IvfPq => Some(AnnIndexType::IvfPq),
+        IndexType::IvfHnswPq => Some(AnnIndexType::IvfHnswPq),
+        IndexType::IvfHnswSq => Some(AnnIndexType::IvfHnswSq),
+        _ => None,
+    }
+}
+
+/// Decides what to run. Pure, so the thresholds can be tested without a table.
+pub fn plan(
+    health: &TableHealth,
+    max_small_fragments: usize,
+    max_unindexed_rows: usize,
+) -> MaintenancePlan {
+    if health.rows == 0 {
+        return MaintenancePlan::default();
+    }
+
+    let rebuild_vector = match health.vector {
+        None => health.rows >= ANN_INDEX_THRESHOLD,
+        // Partitions were trained on less than half of today's rows
+        Some(cov) => cov.unindexed > cov.indexed,
+    };
+    let lagging = [health.vector, health.fts, health.chunk_hash]
+        .iter()
+        .flatten()
+        .any(|cov| cov.unindexed > 0 && cov.unindexed >= max_unindexed_rows);
+
+    MaintenancePlan {
+        compact: health.small_fragments > 0 && health.small_fragments >= max_small_fragments,
+        rebuild_vector,
+        create_fts: health.fts.is_none(),
+        create_chunk_hash: health.chunk_hash.is_none(),
+        optimize_indices: lagging,
+    }
+}
+
+pub async fn table_health(table: &Table) -> Result<TableHealth> {
+    let stats = table.stats().await?;
+    let mut health = TableHealth {
+        rows: stats.num_rows,
+        fragments: stats.fragment_stats.num_fragments,
+        small_fragments: stats.fragment_stats.num_small_fragments,
+        ..Default::default()
+    };
+
+    for index in table.list_indices().await? {
+        let Some(stats) = table.index_stats(&index.name).await? else {
+            continue;
+        };
+        let coverage = Some(IndexCoverage {
+            indexed: stats.num_indexed_rows,
+            unindexed: stats.num_unindexed_rows,
+        });
+        match index.columns.first().map(String::as_str) {
+            Some("vector") => {
+                health.vector = coverage;
+                health.vector_type = ann_index_type(&index.index_type);
+            }
+            Some("content") => health.fts = coverage,
+            Some("chunk_hash") => health.chunk_hash = coverage,
+            _ => {}
+        }
+    }
+    Ok(health)
+}
+
+pub async fn execute(table: &Table, plan: &MaintenancePlan, vectors: &VectorConfig) -> Result<()> {
+    if plan.compact {
+        table
+            .optimize(OptimizeAction::Compact {
+                options: CompactionOptions::default(),
+                remap_options: None,
+            })
+            .await?;
+        table
+            .optimize(OptimizeAction::Prune {
+                older_than: Some(chrono::Duration::hours(VERSION_RETENTION_HOURS)),
+                delete_unverified: Some(false),
+                error_if_tagged_old_versions: None,
+            })
+            .await?;
+    }
+    if plan.rebuild_vector {
+        db::build_ann_index(table, vectors).await?;
+    }
+    if plan.create_fts {
+        db::build_fts_index(table).await?;
+    }
+    if plan.create_chunk_hash {
+        db::build_chunk_hash_index(table).await?;
+    }
+    if plan.optimize_indices {
+        table
+            .optimize(OptimizeAction::Index(OptimizeOptions::default()))
+            .await?;
+    }
+    Ok(())
+}
+
+/// Runs whatever the configured thresholds call for. Returns what was done.
+pub async fn maintain(
+    table: &Table,
+    config: &MaintenanceConfig,
+    vectors: &VectorConfig,
+) -> Result<MaintenancePlan> {
+    let health = table_health(table).await?;
+    let mut plan = plan(&health, config.max_small_fragments, config.max_unindexed_rows);
+    plan.rebuild_vector |= vector_index_outdated(&health, vectors.index);
+    execute(table, &plan, vectors).await?;
+    Ok(plan)
+}
+
+/// Brings every index up to date with the rows a bulk run just wrote.
+/// Compaction is left to the scheduler, it can wait for a quiet moment.
+pub async fn refresh_indexes(table: &Table, vectors: &VectorConfig) -> Result<MaintenancePlan> {
+    let health = table_health(table).await?;
+    let mut plan = MaintenancePlan {
+        compact: false,
+        ..plan(&health, usize::MAX, 1)
+    };
+    plan.rebuild_vector |= vector_index_outdated(&health, vectors.index);
+    execute(table, &plan, vectors).await?;
+    Ok(plan)
+}
+
+/// Background loop: every `interval_minutes`, maintains the table of each container
+/// that no indexing job is currently writing to. Watcher batches wait while a container
+/// is maintained. Config changes apply at the next round.
+pub async fn run_scheduler(
+    db_state: Arc<Mutex<DbState>>,
+    config_state: ConfigState,
+    jobs: JobManager,
+) {
+    loop {
+        let (config, containers) = {
+            let config = config_state.config.lock().await;
+            let containers: Vec<(String, VectorConfig)> = config
+                .containers
+                .iter()
+                .map(|(name, info)| (name.clone(), info.vectors.clone()))
+                .collect();
+            (config.maintenance.clone(), containers)
+        };
+        tokio::time::sleep(Duration::from_secs(config.interval_minutes.max(1) * 60)).await;
+        if !config.enabled {
+            continue;
+        }
+
+        let db = db_state.lock().await.db.clone();
+        for (container, vectors) in containers {
+            // Held for one container at a time, so watcher batches only wait for that one
+            let _watcher = watcher::indexing_lock().lock().await;
+            let busy: HashSet<String> =
+                jobs.list().await.into_iter().map(|j| j.container).collect();
+            if busy.contains(&container) {
+                continue;
+            }
+            let Ok(table) = db.open_table(get_table_name(&container)).execute().await else {
+                continue;
+            };
+            match maintain(&table, &config, &vectors).await {
+                Ok(plan) if !plan.is_empty() => {
+                    eprintln!("maintenance: {}: {}", container, plan.summary());
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("maintenance: {}: {}", container, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn coverage(indexed: usize, unindexed: usize) -> Option<IndexCoverage> {
+        Some(IndexCoverage { indexed, unindexed })
+    }
+
+    fn healthy(rows: usize) -> TableHealth {
+        TableHealth {
+            rows,
+            fragments: 4,
+            small_fragments: 0,
+            vector: coverage(rows, 0),
+            vector_type: None,
+            fts: coverage(rows, 0),
+            chunk_hash: coverage(rows, 0),
+        }
+    }
+
+    #[test]
+    fn test_up_to_date_table_needs_nothing() {
+        assert!(plan(&healthy(10_000), 32, 1_000).is_empty());
+    }
+
+    #[test]
+    fn test_empty_table_needs_nothing() {
+        assert!(plan(&TableHealth::default(), 32, 1_000).is_empty());
+    }
+
+    #[test]
+    fn test_vector_index_waits_for_enough_rows() {
+        let small = TableHealth { vector: None, ..healthy(100) };
+        assert!(!plan(&small, 32, 1_000).rebuild_vector);
+
+        let large = TableHealth { vector: None, ..healthy(ANN_INDEX_THRESHOLD) };
+        assert!(plan(&large, 32, 1_000).rebuild_vector);
+    }
+
+    #[test]
+    fn test_unindexed_rows_trigger_optimize() {
+        let health = TableHealth { fts: coverage(10_000, 1_500), ..healthy(11_500) };
+        let p = plan(&health, 32, 1_000);
+        assert!(p.optimize_indices);
+        assert!(!p.rebuild_vector);
+    }
+
+    #[test]
+    fn test_outgrown_vector_index_is_rebuilt() {
+        let health = TableHealth { vector: coverage(1_000, 3_000), ..healthy(4_000) };
+        assert!(plan(&health, 32, 1_000).rebuild_vector);
+    }
+
+    #[test]
+    fn test_vector_index_of_another_type_is_outdated() {
+        let health = TableHealth { vector_type: Some(AnnIndexType::IvfPq), ..healthy(4_000) };
+        assert!(!vector_index_outdated(&health, AnnIndexType::Auto));
+        assert!(!vector_index_outdated(&health, AnnIndexType::IvfPq));
+        assert!(vector_index_outdated(&health, AnnIndexType::IvfHnswSq));
+
+        let missing = TableHealth { vector: None, ..healthy(100) };
+        assert!(!vector_index_outdated(&missing, AnnIndexType::IvfHnswSq));
+    }
+
+    #[test]
+    fn test_small_fragments_trigger_compaction() {
+        let health = TableHealth { small_fragments: 40, ..healthy(10_000) };
+        assert!(plan(&health, 32, 1_000).compact);
+        assert!(!plan(&health, 64, 1_000).compact);
+    }
+}
@@ -1,37 +1,194 @@
+pub mod archive;
 pub mod chunking;
 pub mod db;
+pub mod doctor;
 pub mod embedding;
 pub mod file_io;
+pub mod filter;
 pub mod git;
+pub mod maintenance;
 pub mod ocr;
+pub mod paths;
 pub mod pipeline;
+pub mod query;
+pub mod reembed;
+pub mod report;
+pub mod rules;
+pub mod schema;
 pub mod search;
 
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{anyhow, Result};
-use arrow_array::RecordBatchIterator;
 use lancedb::connection::Connection;
-use rayon::prelude::*;
-use tokio::sync::Mutex;
+use lancedb::Table;
+use tokio::sync::{mpsc, Mutex};
 
-use crate::config::IndexingConfig;
+use crate::config::{IndexedRoot, IndexingConfig, VectorConfig};
+use crate::jobs::JobControl;
 use crate::state::ModelState;
 
-use ignore::WalkBuilder;
+use embedding::ModelIdentity;
+use file_io::ReadError;
+use ignore::{WalkBuilder, WalkState};
+use filter::Filter;
+use paths::PathMap;
+use report::{FileIssue, IndexReport};
 
 pub use chunking::expand_query;
 pub use db::reset_index;
 pub use embedding::{embed_query, load_model, load_reranker, rerank_results, safe_rerank};
-pub use search::{build_filter_expr, hybrid_merge, search_files, search_fts, search_pipeline};
+pub use query::{parse_query, ParsedQuery};
+pub use search::{
+    build_filter_expr, hybrid_merge, search_files, search_fts, search_pipeline, SearchFilters,
+    SearchHit, SearchTarget,
+};
 
-const ANN_INDEX_THRESHOLD: usize = 256;
 const EMBED_BATCH_SIZE: usize = 256;
+const EXTRACT_QUEUE_DEPTH: usize = 64;
+
+/// How many chunks a write had to embed versus reuse from vectors already in the table.
+#[derive(Debug, Default, Clone, Copy, serde::Serialize, serde::Deserialize)]
+pub struct ChunkStats {
+    pub embedded: usize,
+    pub reused: usize,
+}
+
+impl std::ops::AddAssign for ChunkStats {
+    fn add_assign(&mut self, other: Self) {
+        self.embedded += other.embedded;
+        self.reused += other.reused;
+    }
+}
 
 struct ExtractedFile {
     path: String,
-    chunks: Vec<String>,
+    chunks: Vec<chunking::Chunk>,
     mtime: i64,
+    content_hash: String,
+    file_size: i64,
+    extension: String,
+}
+
+impl ExtractedFile {
+    fn into_pending(self) -> Vec<db::PendingChunk> {
+        let Self {
+            path,
+            chunks,
+            mtime,
+            content_hash,
+            file_size,
+            extension,
+        } = self;
+        chunks
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_index, chunk)| db::PendingChunk {
+                path: path.clone(),
+                content_hash: content_hash.clone(),
+                meta: db::ChunkMeta {
+                    chunk_index: chunk_index as i32,
+                    start_byte: chunk.start_byte as i64,
+                    end_byte: chunk.end_byte as i64,
+                    start_line: chunk.start_line as i32,
+                    end_line: chunk.end_line as i32,
+                    file_size,
+                    extension: extension.clone(),
+                    mtime,
+                    indexed_at: 0,
+                },
+                content: chunk.content,
+            })
+            .collect()
+    }
+}
+
+enum Extraction {
+    Changed(ExtractedFile),
+    Unchanged,
+    /// Same content under a new mtime, written back so the stored one stays current.
+    Touched(i64),
+    Unreadable(ReadError),
+}
+
+/// What the walker threads saw, besides the files they queued for embedding.
+#[derive(Default)]
+struct WalkTally {
+    discovered: AtomicUsize,
+    unchanged: AtomicUsize,
+    unsupported: AtomicUsize,
+    seen: std::sync::Mutex<HashSet<String>>,
+    touched: std::sync::Mutex<HashMap<String, i64>>,
+    unwalked: std::sync::Mutex<Unwalked>,
+    skipped: std::sync::Mutex<(Vec<FileIssue>, usize)>,
+    failed: std::sync::Mutex<(Vec<FileIssue>, usize)>,
+}
+
+impl WalkTally {
+    fn record(&self, path: String, error: ReadError) {
+        let (issues, reason) = match error {
+            ReadError::Unsupported => {
+                self.unsupported.fetch_add(1, Ordering::Relaxed);
+                return;
+            }
+            ReadError::Skipped(reason) => (&self.skipped, reason),
+            ReadError::Failed(reason) => (&self.failed, reason),
+        };
+        let mut issues = lock(issues);
+        let (listed, unlisted) = &mut *issues;
+        report::list_issues(listed, unlisted, vec![FileIssue { path, reason }]);
+    }
+}
+
+/// Parts of a root the walk couldn't read. Their rows are kept rather than pruned: the
+/// files may only be unreachable this time (permissions, a transient I/O error).
+#[derive(Default)]
+struct Unwalked {
+    /// Stored paths of the unreadable files and directories.
+    paths: Vec<String>,
+    /// Set by an error that names no path: nothing under the root is pruned.
+    whole_root: bool,
+}
+
+impl Unwalked {
+    fn record(&mut self, err: &ignore::Error, paths: &PathMap) {
+        match error_path(err).and_then(|p| paths.store(p)) {
+            Some(stored) => self.paths.push(stored.trim_end_matches('/').to_string()),
+            None => self.whole_root = true,
+        }
+    }
+
+    fn covers(&self, stored: &str) -> bool {
+        self.whole_root
+            || self.paths.iter().any(|p| {
+                stored
+                    .strip_prefix(p.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+    }
+}
+
+fn error_path(err: &ignore::Error) -> Option<&Path> {
+    match err {
+        ignore::Error::WithPath { path, .. } => Some(path),
+        ignore::Error::Loop { child, .. } => Some(child),
+        ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+            error_path(err)
+        }
+        _ => None,
+    }
+}
+
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn elapsed_ms(since: Instant) -> u64 {
+    since.elapsed().as_millis() as u64
 }
 
 async fn embed_batch(
@@ -46,7 +203,11 @@ async fn embed_batch(
     embedding::embed_passages(model, texts)
 }
 
-async fn get_model_dim(model_state: &Arc<Mutex<ModelState>>) -> Result<usize> {
+async fn get_model_identity(model_state: &Arc<Mutex<ModelState>>) -> ModelIdentity {
+    model_state.lock().await.identity.clone()
+}
+
+pub async fn get_model_dim(model_state: &Arc<Mutex<ModelState>>) -> Result<usize> {
     let mut guard = model_state.lock().await;
     if let Some(dim) = guard.cached_dim {
         return Ok(dim);
@@ -60,276 +221,479 @@ async fn get_model_dim(model_state: &Arc<Mutex<ModelState>>) -> Result<usize> {
     Ok(dim)
 }
 
+/// Indexes the files under `root`. `paths` maps every root of the container, so a
+/// file also under a nested root is stored relative to that one.
 pub async fn index_directory<F>(
-    root_dir: &str,
+    root: &IndexedRoot,
+    paths: &PathMap,
     table_name: &str,
     db: &Connection,
     model_state: &Arc<Mutex<ModelState>>,
     indexing_config: &IndexingConfig,
+    globs: &[String],
+    vectors: &VectorConfig,
+    job: &JobControl,
     progress_callback: F,
-) -> Result<usize>
+) -> Result<IndexReport>
 where
     F: Fn(usize, usize, String) + Send + Sync + 'static,
 {
+    let started = Instant::now();
+    let walker = walk_builder(&root.path, globs)?;
     let dim = get_model_dim(model_state).await?;
-    let table = db::get_or_create_table(db, table_name, dim).await?;
-
-    let existing_mtimes = db::get_indexed_mtimes(&table).await.unwrap_or_default();
+    let model = get_model_identity(model_state).await;
+    let table = db::get_or_create_table(db, table_name, dim, &model, vectors).await?;
+    // Rows stored before this root or a nested one was added would never match the walk
+    paths::rebase(&table, paths, paths).await?;
+
+    let mut existing = db::get_indexed_fingerprints(&table).await.unwrap_or_default();
+    for path in job.interrupted_files() {
+        existing.remove(path);
+    }
+    let existing = Arc::new(existing);
+
+    progress_callback(0, 0, "Scanning files...".to_string());
+
+    // Walk + extraction run on the walker threads and feed a bounded queue, so
+    // memory stays flat and the first batches land while the walk continues.
+    let (file_tx, mut file_rx) = mpsc::channel::<ExtractedFile>(EXTRACT_QUEUE_DEPTH);
+    let tally = Arc::new(WalkTally::default());
+
+    let producer = {
+        let indexing_config = indexing_config.clone();
+        let paths = paths.clone();
+        let existing = existing.clone();
+        let tally = tally.clone();
+        let job = job.clone();
+        let rt = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || {
+            extract_files(walker, &indexing_config, &paths, &existing, &tally, &job, &rt, file_tx)
+        })
+    };
 
-    let all_files: Vec<_> = WalkBuilder::new(root_dir)
-        .hidden(true)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .add_custom_ignore_filename(".rcignore")
-        .build()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().map_or(false, |ft| ft.is_file()))
-        .map(|e| e.into_path())
-        .collect();
-    let total_files = all_files.len();
+    // Batches are cut on file boundaries so every file's chunks commit together.
+    // Pause and cancel are honoured between batches, never in the middle of one.
+    let mut pending_paths: Vec<String> = Vec::new();
+    let mut pending_chunks: Vec<db::PendingChunk> = Vec::new();
+    let mut files_indexed = 0;
+    let mut indexed_sample: Vec<String> = Vec::new();
+    let mut batches_written = 0;
+    let mut chunk_stats = ChunkStats::default();
+    let mut embed_ms = 0;
 
-    progress_callback(0, total_files, "Scanning files...".to_string());
+    while let Some(ef) = file_rx.recv().await {
+        files_indexed += 1;
+        if indexed_sample.len() < report::INDEXED_SAMPLE {
+            indexed_sample.push(paths.resolve(&ef.path));
+        }
+        pending_paths.push(ef.path.clone());
+        pending_chunks.extend(ef.into_pending());
 
-    let image_files: Vec<_> = all_files
-        .iter()
-        .filter(|p| {
-            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            ocr::is_image_extension(&ext)
-        })
-        .cloned()
-        .collect();
+        if pending_chunks.len() >= EMBED_BATCH_SIZE {
+            job.checkpoint().await?;
+            job.set_pending(&pending_paths);
+            batches_written += 1;
+            progress_callback(
+                files_indexed,
+                tally.discovered.load(Ordering::Relaxed),
+                format!("Embedding batch {}", batches_written),
+            );
+            let batch_started = Instant::now();
+            chunk_stats += write_files(
+                &table,
+                model_state,
+                std::mem::take(&mut pending_paths),
+                std::mem::take(&mut pending_chunks),
+            )
+            .await?;
+            embed_ms += elapsed_ms(batch_started);
+        }
+    }
 
-    let non_image_files: Vec<_> = all_files
-        .iter()
-        .filter(|p| {
-            let ext = p.extension().and_then(|s| s.to_str()).unwrap_or("").to_lowercase();
-            !ocr::is_image_extension(&ext)
-        })
-        .cloned()
-        .collect();
+    producer
+        .await
+        .map_err(|e| anyhow!("File walker failed: {}", e))?;
+    // A cancelled walk stops early and must not be mistaken for a complete one below.
+    job.checkpoint().await?;
+    let walk_ms = elapsed_ms(started);
 
-    let extracted: Vec<ExtractedFile> = non_image_files
-        .par_iter()
-        .filter_map(|path| {
-            let path_str = path.to_string_lossy().to_string();
-            let mtime = file_io::get_file_mtime(path);
+    let total_files = tally.discovered.load(Ordering::Relaxed);
 
-            if let Some(&existing_mtime) = existing_mtimes.get(&path_str) {
-                if existing_mtime == mtime {
-                    return None;
-                }
-            }
+    if !pending_paths.is_empty() {
+        job.checkpoint().await?;
+        job.set_pending(&pending_paths);
+        batches_written += 1;
+        progress_callback(
+            files_indexed,
+            total_files,
+            format!("Embedding batch {}", batches_written),
+        );
+        let batch_started = Instant::now();
+        chunk_stats += write_files(&table, model_state, pending_paths, pending_chunks).await?;
+        embed_ms += elapsed_ms(batch_started);
+    }
 
-            let mut text = file_io::read_file_content_with_config(path, indexing_config)?;
-            if text.trim().is_empty() {
-                return None;
-            }
-            if indexing_config.use_git_history {
-                if let Some(git_ctx) = git::get_commit_context(path) {
-                    text.push_str(&git_ctx);
-                }
-            }
+    let touched = std::mem::take(&mut *lock(&tally.touched));
+    db::update_mtimes(&table, &touched).await?;
+    let seen = std::mem::take(&mut *lock(&tally.seen));
+    let unwalked = std::mem::take(&mut *lock(&tally.unwalked));
+    let pruned = prune_missing(&table, root, &seen, &unwalked).await?;
 
-            let ext = path
-                .extension()
-                .and_then(|s| s.to_str())
-                .unwrap_or("")
-                .to_lowercase();
-            let chunks = chunking::semantic_chunk_with_overrides(
-                &text,
-                &ext,
-                indexing_config.chunk_size,
-                indexing_config.chunk_overlap,
-            );
+    job.root_done(&root.path);
 
-            Some(ExtractedFile {
-                path: path_str,
-                chunks,
-                mtime,
-            })
-        })
-        .collect();
+    let index_started = Instant::now();
+    if files_indexed == 0 {
+        progress_callback(total_files, total_files, "Done -- no new files".to_string());
+    } else {
+        progress_callback(files_indexed, files_indexed, "Updating search indexes...".to_string());
+        if let Err(e) = maintenance::refresh_indexes(&table, vectors).await {
+            eprintln!("indexer: index refresh for {}: {}", table_name, e);
+        }
+    }
 
-    // Parallel OCR for image files
-    let mut image_futures = Vec::new();
-    for path in &image_files {
-        let path_str = path.to_string_lossy().to_string();
-        let mtime = file_io::get_file_mtime(path);
+    Ok(IndexReport {
+        indexed: files_indexed,
+        indexed_sample,
+        unchanged: tally.unchanged.load(Ordering::Relaxed),
+        unsupported: tally.unsupported.load(Ordering::Relaxed),
+        skipped: std::mem::take(&mut lock(&tally.skipped).0),
+        skipped_unlisted: lock(&tally.skipped).1,
+        failed: std::mem::take(&mut lock(&tally.failed).0),
+        failed_unlisted: lock(&tally.failed).1,
+        pruned,
+        chunks: chunk_stats,
+        timings: report::Timings {
+            walk_ms,
+            embed_ms,
+            index_ms: elapsed_ms(index_started),
+            total_ms: elapsed_ms(started),
+        },
+        finished_at: chrono::Utc::now().timestamp(),
+    })
+}
 
-        if let Some(&existing_mtime) = existing_mtimes.get(&path_str) {
-            if existing_mtime == mtime {
-                continue;
-            }
-        }
+/// Removes rows of files under `root` that are gone from disk, without re-indexing anything.
+pub async fn prune_directory(
+    root: &IndexedRoot,
+    paths: &PathMap,
+    globs: &[String],
+    table_name: &str,
+    db: &Connection,
+) -> Result<usize> {
+    let walker = walk_builder(&root.path, globs)?;
+    let table = match db.open_table(table_name).execute().await {
+        Ok(t) => t,
+        Err(_) => return Ok(0),
+    };
 
-        let path_clone = path.clone();
-        let use_git = indexing_config.use_git_history;
-        let chunk_size = indexing_config.chunk_size;
-        let chunk_overlap = indexing_config.chunk_overlap;
-        image_futures.push(tokio::spawn(async move {
-            if let Some(mut text) = file_io::read_file_content_with_ocr(&path_clone).await {
-                if !text.trim().is_empty() {
-                    if use_git {
-                        if let Some(git_ctx) = git::get_commit_context(&path_clone) {
-                            text.push_str(&git_ctx);
-                        }
-                    }
-                    let ext = path_clone
-                        .extension()
-                        .and_then(|s| s.to_str())
-                        .unwrap_or("")
-                        .to_lowercase();
-                    let chunks = chunking::semantic_chunk_with_overrides(&text, &ext, chunk_size, chunk_overlap);
-                    return Some(ExtractedFile {
-                        path: path_clone.to_string_lossy().to_string(),
-                        chunks,
-                        mtime,
-                    });
+    let walk_paths = paths.clone();
+    let (seen, unwalked) = tokio::task::spawn_blocking(move || {
+        let mut seen = HashSet::new();
+        let mut unwalked = Unwalked::default();
+        for entry in walker.build() {
+            match entry {
+                Ok(e) if e.file_type().map_or(false, |ft| ft.is_file()) => {
+                    seen.extend(walk_paths.store(e.path()));
                 }
+                Ok(_) => {}
+                Err(err) => unwalked.record(&err, &walk_paths),
             }
-            None
-        }));
+        }
+        (seen, unwalked)
+    })
+    .await
+    .map_err(|e| anyhow!("File walker failed: {}", e))?;
+
+    let pruned = prune_missing(&table, root, &seen, &unwalked).await?;
+    if pruned > 0 {
+        let _ = db::build_fts_index(&table).await;
     }
+    Ok(pruned)
+}
 
-    let image_results = futures::future::join_all(image_futures).await;
-    let image_extracted: Vec<ExtractedFile> = image_results
-        .into_iter()
-        .filter_map(|r| r.ok().flatten())
-        .collect();
+/// Drops every row stored under `root`, once files that also belong to a remaining root
+/// (one around it) have been moved over to it. `before` still maps `root`, `after` doesn't.
+pub async fn remove_directory(
+    root: &IndexedRoot,
+    before: &PathMap,
+    after: &PathMap,
+    table_name: &str,
+    db: &Connection,
+) -> Result<usize> {
+    let table = match db.open_table(table_name).execute().await {
+        Ok(t) => t,
+        Err(_) => return Ok(0),
+    };
 
-    let mut all_extracted = extracted;
-    all_extracted.extend(image_extracted);
-    let files_indexed = all_extracted.len();
+    schema::migrate(db, &table, table_name).await?;
+    paths::rebase(&table, before, after).await?;
+    let prefix = paths::root_prefix(root);
+    let doomed: Vec<String> = db::get_indexed_mtimes(&table)
+        .await?
+        .into_keys()
+        .filter(|p| p.starts_with(&prefix))
+        .collect();
+    db::delete_paths(&table, &doomed).await?;
+    if !doomed.is_empty() {
+        let _ = db::build_fts_index(&table).await;
+    }
+    Ok(doomed.len())
+}
 
-    if files_indexed == 0 {
-        progress_callback(total_files, total_files, "Done -- no new files".to_string());
+/// Deletes paths stored under `root` that are not in `seen`: deleted, moved, or now ignored.
+/// A root that is missing altogether (unplugged drive, unmounted share) is left untouched,
+/// and so are the parts the walk couldn't read.
+async fn prune_missing(
+    table: &Table,
+    root: &IndexedRoot,
+    seen: &HashSet<String>,
+    unwalked: &Unwalked,
+) -> Result<usize> {
+    if !Path::new(&root.path).is_dir() || unwalked.whole_root {
         return Ok(0);
     }
+    let prefix = paths::root_prefix(root);
+    let orphans: Vec<String> = db::get_indexed_mtimes(table)
+        .await?
+        .into_keys()
+        .filter(|p| !seen.contains(p) && p.starts_with(&prefix) && !unwalked.covers(p))
+        .collect();
+    db::delete_paths(table, &orphans).await?;
+    Ok(orphans.len())
+}
 
-    progress_callback(
-        0,
-        files_indexed,
-        format!("Extracted {} files, starting embedding...", files_indexed),
-    );
+/// Walker over `root_dir` honouring ignore files and the container's globs.
+fn walk_builder(root_dir: &str, globs: &[String]) -> Result<WalkBuilder> {
+    let mut builder = WalkBuilder::new(root_dir);
+    builder
+        .hidden(true)
+        .git_ignore(true)
+        .git_global(true)
+        .git_exclude(true)
+        .add_custom_ignore_filename(".rcignore")
+        .overrides(rules::build_override(root_dir, globs)?);
+    Ok(builder)
+}
 
-    let mut pending_chunks: Vec<db::PendingChunk> = Vec::new();
-    let mut batches_written = 0;
+/// Walks the root in parallel and pushes every new or modified file into `file_tx`.
+/// Blocks on the queue when the embedder falls behind; stops early once the receiver is gone
+/// or the job is cancelled.
+fn extract_files(
+    walker: WalkBuilder,
+    indexing_config: &IndexingConfig,
+    paths: &PathMap,
+    existing: &HashMap<String, db::FileFingerprint>,
+    tally: &WalkTally,
+    job: &JobControl,
+    rt: &tokio::runtime::Handle,
+    file_tx: mpsc::Sender<ExtractedFile>,
+) {
+    walker
+        .build_parallel()
+        .run(|| {
+            let file_tx = file_tx.clone();
+            Box::new(move |entry| {
+                if job.is_cancelled() {
+                    return WalkState::Quit;
+                }
+                let entry = match entry {
+                    Ok(e) => e,
+                    Err(err) => {
+                        lock(&tally.unwalked).record(&err, paths);
+                        return WalkState::Continue;
+                    }
+                };
+                if !entry.file_type().map_or(false, |ft| ft.is_file()) {
+                    return WalkState::Continue;
+                }
+                let Some(stored) = paths.store(entry.path()) else {
+                    return WalkState::Continue;
+                };
+                let path = entry.path().to_string_lossy().to_string();
+                tally.discovered.fetch_add(1, Ordering::Relaxed);
+                lock(&tally.seen).insert(stored.clone());
+
+                match extract_file(entry.path(), stored.clone(), indexing_config, existing, rt) {
+                    Extraction::Changed(ef) => {
+                        if file_tx.blocking_send(ef).is_err() {
+                            return WalkState::Quit;
+                        }
+                    }
+                    Extraction::Unchanged => {
+                        tally.unchanged.fetch_add(1, Ordering::Relaxed);
+                    }
+                    Extraction::Touched(mtime) => {
+                        tally.unchanged.fetch_add(1, Ordering::Relaxed);
+                        lock(&tally.touched).insert(stored, mtime);
+                    }
+                    Extraction::Unreadable(error) => tally.record(path, error),
+                }
+                WalkState::Continue
+            })
+        });
+}
 
-    for (idx, ef) in all_extracted.iter().enumerate() {
-        let safe_path = ef.path.replace('\'', "''");
-        let _ = table.delete(&format!("path = '{}'", safe_path)).await;
+/// `stored` is the path's form in the table, see [`PathMap::store`].
+fn extract_file(
+    path: &Path,
+    stored: String,
+    indexing_config: &IndexingConfig,
+    existing: &HashMap<String, db::FileFingerprint>,
+    rt: &tokio::runtime::Handle,
+) -> Extraction {
+    let reader = match file_io::reader_for(path, indexing_config) {
+        Ok(reader) => reader,
+        Err(e) => return Extraction::Unreadable(e),
+    };
 
-        for chunk in &ef.chunks {
-            pending_chunks.push(db::PendingChunk {
-                path: ef.path.clone(),
-                content: chunk.clone(),
-                mtime: ef.mtime,
-            });
-        }
+    let mtime = file_io::get_file_mtime(path);
+    let file_size = file_io::get_file_size(path);
+    let fingerprint = existing.get(&stored);
+    if indexing_config.quick_check && fingerprint.is_some_and(|f| f.same_stat(mtime, file_size)) {
+        return Extraction::Unchanged;
+    }
+    let content_hash = match file_io::hash_file(path) {
+        Ok(h) => h,
+        Err(e) => return Extraction::Unreadable(e),
+    };
+    if let Some(f) = fingerprint.filter(|f| f.is_unchanged(mtime, &content_hash)) {
+        return if f.mtime == mtime { Extraction::Unchanged } else { Extraction::Touched(mtime) };
+    }
 
-        if pending_chunks.len() >= EMBED_BATCH_SIZE {
-            batches_written += 1;
-            progress_callback(
-                idx + 1,
-                files_indexed,
-                format!("Embedding batch {}", batches_written),
-            );
+    let ext = path
+        .extension()
+        .and_then(|s| s.to_str())
+        .unwrap_or("")
+        .to_lowercase();
 
-            let batch_chunks: Vec<db::PendingChunk> = pending_chunks.drain(..).collect();
-            let texts: Vec<String> = batch_chunks.iter().map(|c| c.content.clone()).collect();
-            let embeddings = embed_batch(model_state, texts).await?;
-
-            let records: Vec<db::Record> = batch_chunks
-                .into_iter()
-                .zip(embeddings)
-                .map(|(chunk, vector)| db::Record {
-                    path: chunk.path,
-                    content: chunk.content,
-                    vector,
-                    mtime: chunk.mtime,
-                })
-                .collect();
-
-            let batch = db::create_record_batch(records)?;
-            let schema = batch.schema();
-            table
-                .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-                .execute()
-                .await?;
+    let text = match reader {
+        file_io::Reader::Image => rt.block_on(file_io::try_read_image_text(path)),
+        _ => file_io::try_read_file_content(path, indexing_config),
+    };
+    let mut text = match text {
+        Ok(t) if !t.trim().is_empty() => t,
+        Ok(_) => return Extraction::Unreadable(no_text()),
+        Err(e) => return Extraction::Unreadable(e),
+    };
+    if indexing_config.use_git_history {
+        if let Some(git_ctx) = git::get_commit_context(path) {
+            text.push_str(&git_ctx);
         }
     }
 
-    if !pending_chunks.is_empty() {
-        batches_written += 1;
-        progress_callback(
-            files_indexed,
-            files_indexed,
-            format!("Embedding batch {}", batches_written),
-        );
-
-        let texts: Vec<String> = pending_chunks.iter().map(|c| c.content.clone()).collect();
-        let embeddings = embed_batch(model_state, texts).await?;
+    let chunks = chunking::chunk_spans(
+        &text,
+        &ext,
+        indexing_config.chunk_size,
+        indexing_config.chunk_overlap,
+    );
+    if chunks.is_empty() {
+        return Extraction::Unreadable(no_text());
+    }
 
-        let records: Vec<db::Record> = pending_chunks
-            .into_iter()
-            .zip(embeddings)
-            .map(|(chunk, vector)| db::Record {
-                path: chunk.path,
-                content: chunk.content,
-                vector,
-                mtime: chunk.mtime,
-            })
-            .collect();
+    Extraction::Changed(ExtractedFile {
+        path: stored,
+        chunks,
+        mtime,
+        content_hash,
+        file_size,
+        extension: ext,
+    })
+}
 
-        let batch = db::create_record_batch(records)?;
-        let schema = batch.schema();
-        table
-            .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-            .execute()
-            .await?;
-    }
+fn no_text() -> ReadError {
+    ReadError::Skipped("no text content".to_string())
+}
 
-    let total_indexed = files_indexed;
+async fn write_files(
+    table: &Table,
+    model_state: &Arc<Mutex<ModelState>>,
+    paths: Vec<String>,
+    chunks: Vec<db::PendingChunk>,
+) -> Result<ChunkStats> {
+    let hashes: Vec<String> = chunks.iter().map(|c| chunking::chunk_hash(&c.content)).collect();
+
+    // Unchanged chunks (same normalized text, in this file or any other) keep their stored vector.
+    let mut cached = db::get_cached_vectors(table, &hashes)
+        .await
+        .unwrap_or_default();
+    let (missing_hashes, missing_texts): (Vec<String>, Vec<String>) = chunks
+        .iter()
+        .zip(&hashes)
+        .filter(|(_, hash)| !cached.contains_key(*hash))
+        .map(|(chunk, hash)| (hash.clone(), chunk.content.clone()))
+        .unzip();
+
+    let indexed_at = chrono::Utc::now().timestamp();
+    let stats = ChunkStats {
+        embedded: missing_texts.len(),
+        reused: chunks.len() - missing_texts.len(),
+    };
 
-    if total_indexed >= ANN_INDEX_THRESHOLD {
-        progress_callback(files_indexed, files_indexed, "Building vector index...".to_string());
-        let _ = db::build_ann_index(&table).await;
+    if !missing_texts.is_empty() {
+        let embeddings = embed_batch(model_state, missing_texts).await?;
+        cached.extend(missing_hashes.into_iter().zip(embeddings));
     }
 
-    progress_callback(files_indexed, files_indexed, "Building search index...".to_string());
-    let _ = db::build_fts_index(&table).await;
+    let records: Vec<db::Record> = chunks
+        .into_iter()
+        .zip(hashes)
+        .filter_map(|(chunk, chunk_hash)| {
+            let vector = cached.get(&chunk_hash)?.clone();
+            Some(db::Record {
+                path: chunk.path,
+                chunk_hash,
+                content: chunk.content,
+                vector,
+                content_hash: chunk.content_hash,
+                meta: db::ChunkMeta {
+                    indexed_at,
+                    ..chunk.meta
+                },
+            })
+        })
+        .collect();
 
-    Ok(files_indexed)
+    db::replace_files(table, &paths, records).await?;
+    Ok(stats)
 }
 
 pub async fn index_single_file(
     file_path: &std::path::Path,
+    paths: &PathMap,
     table_name: &str,
     db: &Connection,
     model_state: &Arc<Mutex<ModelState>>,
+    vectors: &VectorConfig,
     use_git_history: bool,
     chunk_size: Option<usize>,
     chunk_overlap: Option<usize>,
-) -> Result<bool> {
+) -> Result<Option<ChunkStats>> {
     if !file_path.is_file() {
-        return Ok(false);
+        return Ok(None);
     }
+    let Some(path_str) = paths.store(file_path) else {
+        return Ok(None);
+    };
 
     let dim = get_model_dim(model_state).await?;
-    let table = db::get_or_create_table(db, table_name, dim).await?;
-    let path_str = file_path.to_string_lossy().to_string();
+    let model = get_model_identity(model_state).await;
+    let table = db::get_or_create_table(db, table_name, dim, &model, vectors).await?;
     let mtime = file_io::get_file_mtime(file_path);
-
-    if let Ok(Some(existing_mtime)) = db::get_single_file_mtime(&table, &path_str).await {
-        if existing_mtime == mtime {
-            return Ok(false);
+    let file_size = file_io::get_file_size(file_path);
+    let fingerprint = db::get_single_file_fingerprint(&table, &path_str).await.ok().flatten();
+    // The watcher only calls for files an event named, so they are always hashed
+    let content_hash = match file_io::hash_file(file_path) {
+        Ok(h) => h,
+        Err(_) => return Ok(None),
+    };
+    if let Some(f) = fingerprint.filter(|f| f.is_unchanged(mtime, &content_hash)) {
+        if f.mtime != mtime {
+            db::update_mtimes(&table, &HashMap::from([(path_str, mtime)])).await?;
         }
+        return Ok(None);
     }
 
-    let safe_path = path_str.replace('\'', "''");
-    let _ = table.delete(&format!("path = '{}'", safe_path)).await;
-
     let ext = file_path
         .extension()
         .and_then(|s| s.to_str())
@@ -344,7 +708,10 @@ pub async fn index_single_file(
 
     let mut text = match text {
         Some(t) if !t.trim().is_empty() => t,
-        _ => return Ok(false),
+        _ => {
+            db::replace_files(&table, &[path_str], Vec::new()).await?;
+            return Ok(None);
+        }
     };
     if use_git_history {
         if let Some(git_ctx) = git::get_commit_context(file_path) {
@@ -352,42 +719,112 @@ pub async fn index_single_file(
         }
     }
 
-    let chunks = chunking::semantic_chunk_with_overrides(&text, &ext, chunk_size, chunk_overlap);
+    let chunks = chunking::chunk_spans(&text, &ext, chunk_size, chunk_overlap);
     if chunks.is_empty() {
-        return Ok(false);
+        db::replace_files(&table, &[path_str], Vec::new()).await?;
+        return Ok(None);
     }
 
-    let texts: Vec<String> = chunks.clone();
-    let embeddings = embed_batch(model_state, texts).await?;
-
-    let records: Vec<db::Record> = chunks
-        .into_iter()
-        .zip(embeddings)
-        .map(|(content, vector)| db::Record {
-            path: path_str.clone(),
-            content,
-            vector,
-            mtime,
-        })
-        .collect();
+    let pending = ExtractedFile {
+        path: path_str.clone(),
+        chunks,
+        mtime,
+        content_hash,
+        file_size,
+        extension: ext,
+    }
+    .into_pending();
 
-    let batch = db::create_record_batch(records)?;
-    let schema = batch.schema();
-    table
-        .add(RecordBatchIterator::new(vec![Ok(batch)], schema))
-        .execute()
-        .await?;
+    let stats = write_files(&table, model_state, vec![path_str], pending).await?;
 
-    Ok(true)
+    Ok(Some(stats))
 }
 
 pub async fn delete_file_from_index(
-    file_path: &str,
+    file_path: &Path,
+    paths: &PathMap,
     table_name: &str,
     db: &Connection,
 ) -> Result<()> {
+    let Some(stored) = paths.store(file_path) else {
+        return Ok(());
+    };
     let table = db.open_table(table_name).execute().await?;
-    let safe_path = file_path.replace('\'', "''");
-    table.delete(&format!("path = '{}'", safe_path)).await?;
+    table.delete(&Filter::path_is(&stored).to_sql()).await?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn extract(
+        path: &Path,
+        existing: &HashMap<String, db::FileFingerprint>,
+        config: &IndexingConfig,
+    ) -> Extraction {
+        let rt = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        extract_file(path, "@r/notes.txt".to_string(), config, existing, rt.handle())
+    }
+
+    fn set_mtime(path: &Path, mtime: std::time::SystemTime) {
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_edit_keeping_size_and_mtime_is_reindexed() {
+        let dir = std::env::temp_dir().join("recall_lite_extract_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "first draft").unwrap();
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let existing = HashMap::from([(
+            "@r/notes.txt".to_string(),
+            db::FileFingerprint {
+                mtime: file_io::get_file_mtime(&path),
+                file_size: file_io::get_file_size(&path),
+                content_hash: file_io::hash_file(&path).unwrap(),
+            },
+        )]);
+        let config = IndexingConfig { use_git_history: false, ..Default::default() };
+        assert!(matches!(extract(&path, &existing, &config), Extraction::Unchanged));
+
+        // Same length, mtime put back the way a sync tool would
+        std::fs::write(&path, "final draft").unwrap();
+        set_mtime(&path, modified);
+        assert!(matches!(extract(&path, &existing, &config), Extraction::Changed(_)));
+
+        let quick = IndexingConfig { quick_check: true, ..config };
+        assert!(matches!(extract(&path, &existing, &quick), Extraction::Unchanged));
+    }
+
+    #[test]
+    fn test_touched_file_reports_its_new_mtime() {
+        let dir = std::env::temp_dir().join("recall_lite_touch_test");
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("notes.txt");
+        std::fs::write(&path, "unchanged").unwrap();
+        let existing = HashMap::from([(
+            "@r/notes.txt".to_string(),
+            db::FileFingerprint {
+                mtime: file_io::get_file_mtime(&path),
+                file_size: file_io::get_file_size(&path),
+                content_hash: file_io::hash_file(&path).unwrap(),
+            },
+        )]);
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        let later = modified + std::time::Duration::from_secs(60);
+        set_mtime(&path, later);
+
+        let config = IndexingConfig { use_git_history: false, ..Default::default() };
+        match extract(&path, &existing, &config) {
+            Extraction::Touched(mtime) => assert_eq!(mtime, file_io::get_file_mtime(&path)),
+            _ => panic!("expected the touched file to keep its rows"),
+        }
+    }
+}
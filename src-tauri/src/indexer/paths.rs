@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Result;
+use lancedb::Table;
+
+use super::db;
+use crate::config::IndexedRoot;
+
+/// Starts every root-relative stored path. An absolute path never does.
+const ROOT_MARKER: char = '@';
+
+/// Translates between paths on disk and the paths stored in a container's table.
+///
+/// Rows store `@<root id>/<path relative to the root>`, `/`-separated on every platform,
+/// so a root that moves only needs its new location in the config. Tables written before
+/// roots had ids hold absolute paths until [`rebase`] rewrites them.
+#[derive(Clone, Default)]
+pub struct PathMap {
+    roots: Vec<IndexedRoot>,
+}
+
+impl PathMap {
+    pub fn new(roots: Vec<IndexedRoot>) -> Self {
+        Self { roots }
+    }
+
+    /// Stored form of `path`, relative to the deepest root containing it so a file under
+    /// nested roots keeps a single set of rows. `None` outside every root.
+    pub fn store(&self, path: &Path) -> Option<String> {
+        let (root, relative) = self
+            .roots
+            .iter()
+            .filter_map(|r| path.strip_prefix(&r.path).ok().map(|rel| (r, rel)))
+            .min_by_key(|(_, rel)| rel.components().count())?;
+        let parts: Vec<String> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(part) => Some(part.to_string_lossy().to_string()),
+                _ => None,
+            })
+            .collect();
+        Some(format!("{}{}", root_prefix(root), parts.join("/")))
+    }
+
+    /// Absolute path of a stored one. Legacy absolute paths, and paths under a root
+    /// this map doesn't know, come back unchanged.
+    pub fn resolve(&self, stored: &str) -> String {
+        match self.split(stored) {
+            Some((root, relative)) => relative
+                .split('/')
+                .fold(PathBuf::from(&root.path), |path, part| path.join(part))
+                .to_string_lossy()
+                .to_string(),
+            None => stored.to_string(),
+        }
+    }
+
+    /// Turns a path prefix given by a user into the prefixes matching stored paths: an
+    /// absolute prefix under a root is stored like a path, a relative one is taken from
+    /// every root since it may lie under any of them. Anything else is returned as is.
+    pub fn stored_prefixes(&self, prefix: &str) -> Vec<String> {
+        if Path::new(prefix).is_absolute() {
+            return vec![self.store(Path::new(prefix)).unwrap_or_else(|| prefix.to_string())];
+        }
+        if self.roots.is_empty() {
+            return vec![prefix.to_string()];
+        }
+        let relative = prefix.replace('\\', "/");
+        self.roots
+            .iter()
+            .map(|root| format!("{}{}", root_prefix(root), relative))
+            .collect()
+    }
+
+    fn split<'a>(&self, stored: &'a str) -> Option<(&IndexedRoot, &'a str)> {
+        let (id, relative) = stored.strip_prefix(ROOT_MARKER)?.split_once('/')?;
+        self.roots.iter().find(|r| r.id == id).map(|r| (r, relative))
+    }
+}
+
+/// Prefix shared by every stored path under `root`.
+pub fn root_prefix(root: &IndexedRoot) -> String {
+    format!("{}{}/", ROOT_MARKER, root.id)
+}
+
+/// Re-stores every row whose path reads differently once resolved with `from` and stored
+/// with `to`: absolute paths of older tables, and files that changed roots because one was
+/// added inside or removed from around them. Vectors are kept. Returns the files moved.
+pub async fn rebase(table: &Table, from: &PathMap, to: &PathMap) -> Result<usize> {
+    let renames: HashMap<String, String> = db::get_indexed_mtimes(table)
+        .await?
+        .into_keys()
+        .filter_map(|stored| {
+            let restored = to.store(Path::new(&from.resolve(&stored)))?;
+            (restored != stored).then_some((stored, restored))
+        })
+        .collect();
+    db::rename_paths(table, &renames).await?;
+    Ok(renames.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(roots: &[(&str, &str)]) -> PathMap {
+        PathMap::new(
+            roots
+                .iter()
+                .map(|(id, path)| IndexedRoot {
+                    id: id.to_string(),
+                    path: path.to_string(),
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_store_and_resolve_round_trip() {
+        let paths = map(&[("a1", "/home/me/code")]);
+        let stored = paths.store(Path::new("/home/me/code/src/main.rs")).unwrap();
+        assert_eq!(stored, "@a1/src/main.rs");
+        assert_eq!(paths.resolve(&stored), "/home/me/code/src/main.rs");
+    }
+
+    #[test]
+    fn test_moved_root_resolves_to_new_location() {
+        let stored = map(&[("a1", "/home/me/code")])
+            .store(Path::new("/home/me/code/README.md"))
+            .unwrap();
+        assert_eq!(map(&[("a1", "/mnt/backup/code")]).resolve(&stored), "/mnt/backup/code/README.md");
+    }
+
+    #[test]
+    fn test_store_prefers_nested_root() {
+        let paths = map(&[("outer", "/code"), ("inner", "/code/app")]);
+        assert_eq!(paths.store(Path::new("/code/app/lib.rs")).unwrap(), "@inner/lib.rs");
+        assert_eq!(paths.store(Path::new("/code/notes.md")).unwrap(), "@outer/notes.md");
+        assert_eq!(paths.store(Path::new("/elsewhere/x.rs")), None);
+    }
+
+    #[test]
+    fn test_resolve_passes_legacy_and_unknown_paths_through() {
+        let paths = map(&[("a1", "/code")]);
+        assert_eq!(paths.resolve("/code/old.rs"), "/code/old.rs");
+        assert_eq!(paths.resolve("@gone/x.rs"), "@gone/x.rs");
+    }
+
+    #[test]
+    fn test_stored_prefixes() {
+        let single = map(&[("a1", "/code")]);
+        assert_eq!(single.stored_prefixes("src/indexer"), vec!["@a1/src/indexer"]);
+        assert_eq!(single.stored_prefixes("/code/src"), vec!["@a1/src"]);
+        assert_eq!(PathMap::default().stored_prefixes("src"), vec!["src"]);
+    }
+
+    #[test]
+    fn test_relative_prefix_applies_under_every_root() {
+        let several = map(&[("a1", "/code"), ("b2", "/docs")]);
+        assert_eq!(several.stored_prefixes("src"), vec!["@a1/src", "@b2/src"]);
+        assert_eq!(several.stored_prefixes("/docs/guides"), vec!["@b2/guides"]);
+    }
+}
@@ -1,14 +1,30 @@
 use serde::Serialize;
 
+use super::db::ChunkMeta;
+use super::search::SearchHit;
+
 #[derive(Serialize, Clone)]
 pub struct ScoredResult {
+    pub container: String,
     pub path: String,
     pub snippet: String,
     pub score: f32,
+    #[serde(flatten)]
+    pub meta: ChunkMeta,
+}
+
+fn to_scored(hit: SearchHit, score: f32) -> ScoredResult {
+    ScoredResult {
+        container: hit.container,
+        path: hit.path,
+        snippet: hit.content,
+        score,
+        meta: hit.meta,
+    }
 }
 
 pub fn score_results(
-    final_results: Vec<(String, String, f32)>,
+    final_results: Vec<SearchHit>,
     used_reranker: bool,
     used_hybrid: bool,
     max_results: usize,
@@ -16,42 +32,30 @@ pub fn score_results(
     let mut scored: Vec<ScoredResult> = if used_reranker {
         final_results
             .into_iter()
-            .map(|(path, snippet, raw_score)| {
-                let sigmoid = 1.0 / (1.0 + (-raw_score).exp());
-                ScoredResult {
-                    path,
-                    snippet,
-                    score: sigmoid * 100.0,
-                }
+            .map(|hit| {
+                let sigmoid = 1.0 / (1.0 + (-hit.score).exp());
+                to_scored(hit, sigmoid * 100.0)
             })
             .collect()
     } else if used_hybrid {
-        let max_rrf = final_results.first().map(|(_, _, s)| *s).unwrap_or(1.0);
+        let max_rrf = final_results.first().map(|hit| hit.score).unwrap_or(1.0);
         final_results
             .into_iter()
-            .map(|(path, snippet, rrf_score)| {
+            .map(|hit| {
                 let pct = if max_rrf > 0.0 {
-                    (rrf_score / max_rrf) * 100.0
+                    (hit.score / max_rrf) * 100.0
                 } else {
                     0.0
                 };
-                ScoredResult {
-                    path,
-                    snippet,
-                    score: pct,
-                }
+                to_scored(hit, pct)
             })
             .collect()
     } else {
         final_results
             .into_iter()
-            .map(|(path, snippet, cosine_dist)| {
-                let similarity = (1.0 - cosine_dist).clamp(0.0, 1.0);
-                ScoredResult {
-                    path,
-                    snippet,
-                    score: similarity * 100.0,
-                }
+            .map(|hit| {
+                let similarity = (1.0 - hit.score).clamp(0.0, 1.0);
+                to_scored(hit, similarity * 100.0)
             })
             .collect()
     };
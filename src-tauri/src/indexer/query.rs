@@ -0,0 +1,307 @@
+use chrono::{DateTime, Local, NaiveDate};
+
+use super::search::SearchFilters;
+
+/// A search bar query split into the text to search for and the filters typed inline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ParsedQuery {
+    /// What gets embedded and matched by BM25: the plain words and the words of quoted phrases.
+    pub text: String,
+    /// `in:` is kept as typed; map it to a stored prefix before searching.
+    pub filters: SearchFilters,
+}
+
+/// Splits on whitespace, keeping a quoted span in one token, quotes included.
+fn tokens(input: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in input.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+fn unquote(s: &str) -> &str {
+    s.trim_matches('"')
+}
+
+/// Midnight at the start of `date`, local time, in Unix seconds.
+fn day_start(date: NaiveDate) -> Option<i64> {
+    date.and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|t| t.timestamp())
+}
+
+/// Last second of `date`, local time.
+fn day_end(date: NaiveDate) -> Option<i64> {
+    day_start(date.succ_opt()?).map(|t| t - 1)
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+/// Seconds in a duration like `30s`, `15m`, `2h`, `7d` or `2w`. `None` past `u64`.
+pub fn parse_duration(s: &str) -> Option<u64> {
+    let s = s.trim().to_lowercase();
+    let (num_str, multiplier) = if let Some(n) = s.strip_suffix('s') {
+        (n, 1u64)
+    } else if let Some(n) = s.strip_suffix('m') {
+        (n, 60)
+    } else if let Some(n) = s.strip_suffix('h') {
+        (n, 3600)
+    } else if let Some(n) = s.strip_suffix('d') {
+        (n, 86400)
+    } else if let Some(n) = s.strip_suffix('w') {
+        (n, 604800)
+    } else {
+        return None;
+    };
+    num_str.trim().parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+/// The Unix time `seconds` before `timestamp`, `None` when that is out of range.
+pub fn seconds_before(timestamp: i64, seconds: u64) -> Option<i64> {
+    timestamp.checked_sub(i64::try_from(seconds).ok()?)
+}
+
+/// Modification times a search is limited to, in Unix seconds, both ends inclusive.
+/// `None` leaves that end open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimeWindow {
+    pub after: Option<i64>,
+    pub before: Option<i64>,
+}
+
+impl TimeWindow {
+    fn days(first: NaiveDate, last: NaiveDate) -> Option<Self> {
+        Some(Self {
+            after: Some(day_start(first)?),
+            before: Some(day_end(last)?),
+        })
+    }
+
+    /// Parses `last 7d` or `7d` (any unit of [`parse_duration`]), `today`, `yesterday`,
+    /// a day like `2025-01-31`, or a range `2025-01-01..2025-01-31` with either end
+    /// left out. Days are local and count whole.
+    pub fn parse(spec: &str, now: DateTime<Local>) -> Option<Self> {
+        let spec = spec.trim().to_lowercase();
+        let today = now.date_naive();
+        match spec.as_str() {
+            "today" => return Self::days(today, today),
+            "yesterday" => return Self::days(today.pred_opt()?, today.pred_opt()?),
+            _ => {}
+        }
+        if let Some((first, last)) = spec.split_once("..") {
+            let after = match first.trim() {
+                "" => None,
+                day => Some(day_start(parse_date(day)?)?),
+            };
+            let before = match last.trim() {
+                "" => None,
+                day => Some(day_end(parse_date(day)?)?),
+            };
+            return (after.is_some() || before.is_some()).then_some(Self { after, before });
+        }
+        if let Some(day) = parse_date(&spec) {
+            return Self::days(day, day);
+        }
+        let relative = spec.strip_prefix("last").unwrap_or(&spec);
+        let seconds = parse_duration(relative)?;
+        Some(Self {
+            after: Some(seconds_before(now.timestamp(), seconds)?),
+            before: None,
+        })
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.after.is_none() && self.before.is_none()
+    }
+}
+
+/// A bound of `after:` / `before:`: the start or end of a day, or a duration back from `now`.
+fn parse_bound(value: &str, now: DateTime<Local>, end_of_day: bool) -> Option<i64> {
+    match parse_date(value) {
+        Some(day) if end_of_day => day_end(day),
+        Some(day) => day_start(day),
+        None => seconds_before(now.timestamp(), parse_duration(value)?),
+    }
+}
+
+/// Applies `key:value` to `filters`. False when the key is unknown or the value doesn't
+/// parse, and the token is searched for as text instead.
+fn apply_filter(key: &str, value: &str, now: DateTime<Local>, filters: &mut SearchFilters) -> bool {
+    match key {
+        "ext" => {
+            let extensions: Vec<String> = value
+                .split(',')
+                .map(|e| e.trim().trim_start_matches('.'))
+                .filter(|e| !e.is_empty())
+                .map(str::to_string)
+                .collect();
+            if extensions.is_empty() {
+                return false;
+            }
+            filters.extensions.extend(extensions);
+        }
+        "in" if !value.is_empty() => filters.path_prefixes = vec![value.to_string()],
+        "after" => match parse_bound(value, now, false) {
+            Some(start) => filters.modified.get_or_insert_with(TimeWindow::default).after = Some(start),
+            None => return false,
+        },
+        // Inclusive: the whole day counts
+        "before" => match parse_bound(value, now, true) {
+            Some(end) => filters.modified.get_or_insert_with(TimeWindow::default).before = Some(end),
+            None => return false,
+        },
+        "modified" => match TimeWindow::parse(value, now) {
+            Some(window) => filters.modified = Some(window),
+            None => return false,
+        },
+        _ => return false,
+    }
+    true
+}
+
+/// Parses the filters typed inline in a query:
+///
+/// - `ext:rs` or `ext:rs,ts`: file extensions, repeatable
+/// - `in:src/indexer` or `in:"My Docs"`: path prefix, absolute or relative to the folder
+/// - `after:2025-01-01`, `before:2025-01-31`: modification date, both days included.
+///   A duration works too: `after:7d` is the last week, `before:30d` older than a month
+/// - `modified:"last 7d"`, `modified:2025-01-01..2025-01-31`: a whole [`TimeWindow`]
+/// - `"exact phrase"`: the chunk must contain it, and its words are searched for
+/// - `-term` or `-"some phrase"`: the chunk must not contain it
+///
+/// Anything else, including a filter whose value doesn't parse, is searched for as text.
+pub fn parse_query(input: &str) -> ParsedQuery {
+    let now = Local::now();
+    let mut filters = SearchFilters::default();
+    let mut words: Vec<String> = Vec::new();
+
+    for token in tokens(input) {
+        let (negated, body) = match token.strip_prefix('-') {
+            Some(rest) if !rest.is_empty() => (true, rest),
+            _ => (false, token.as_str()),
+        };
+        if !negated && !body.starts_with('"') {
+            if let Some((key, value)) = body.split_once(':') {
+                if apply_filter(key, unquote(value), now, &mut filters) {
+                    continue;
+                }
+            }
+        }
+        let term = unquote(body).trim();
+        if term.is_empty() {
+            continue;
+        }
+        if negated {
+            filters.excluded.push(term.to_string());
+            continue;
+        }
+        if body.starts_with('"') {
+            filters.phrases.push(term.to_string());
+        }
+        words.push(term.to_string());
+    }
+
+    ParsedQuery {
+        text: words.join(" "),
+        filters,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_query_is_untouched() {
+        let parsed = parse_query("  how does  hybrid merge work ");
+        assert_eq!(parsed.text, "how does hybrid merge work");
+        assert_eq!(parsed.filters, SearchFilters::default());
+    }
+
+    #[test]
+    fn test_filters_are_taken_out_of_the_text() {
+        let parsed = parse_query(r#"ext:rs,.ts in:"src/my indexer" "exact phrase" merge -todo -"not this""#);
+        assert_eq!(parsed.text, "exact phrase merge");
+        assert_eq!(parsed.filters.extensions, vec!["rs", "ts"]);
+        assert_eq!(parsed.filters.path_prefixes, vec!["src/my indexer"]);
+        assert_eq!(parsed.filters.phrases, vec!["exact phrase"]);
+        assert_eq!(parsed.filters.excluded, vec!["todo", "not this"]);
+    }
+
+    #[test]
+    fn test_date_range_covers_whole_days() {
+        let parsed = parse_query("after:2025-01-01 before:2025-01-01 report");
+        let window = parsed.filters.modified.unwrap();
+        assert_eq!(window.before.unwrap() - window.after.unwrap(), 86_399);
+        assert_eq!(parsed.text, "report");
+    }
+
+    #[test]
+    fn test_time_window_specs() {
+        let now = day_start(NaiveDate::from_ymd_opt(2025, 3, 12).unwrap())
+            .and_then(|t| DateTime::from_timestamp(t + 15 * 3600, 0))
+            .unwrap()
+            .with_timezone(&Local);
+        let ts = now.timestamp();
+
+        assert_eq!(
+            TimeWindow::parse("last 7d", now),
+            Some(TimeWindow { after: Some(ts - 7 * 86400), before: None })
+        );
+        assert_eq!(TimeWindow::parse("2h", now).unwrap().after, Some(ts - 7200));
+        assert_eq!(TimeWindow::parse("today", now).unwrap().after, Some(ts - 15 * 3600));
+        assert_eq!(
+            TimeWindow::parse("yesterday", now),
+            TimeWindow::parse("2025-03-11", now)
+        );
+        let open_start = TimeWindow::parse("..2025-03-01", now).unwrap();
+        assert_eq!(open_start.after, None);
+        assert!(open_start.before.is_some());
+        assert_eq!(TimeWindow::parse("..", now), None);
+        assert_eq!(TimeWindow::parse("soon", now), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("30m"), Some(1800));
+        assert_eq!(parse_duration(" 2W "), Some(1_209_600));
+        assert_eq!(parse_duration("7"), None);
+        assert_eq!(parse_duration("99999999999999999w"), None);
+    }
+
+    #[test]
+    fn test_huge_durations_do_not_parse() {
+        let now = Local::now();
+        assert_eq!(TimeWindow::parse("last 18446744073709551615s", now), None);
+        assert_eq!(seconds_before(i64::MIN + 1, 2), None);
+        let parsed = parse_query("after:9223372036854775808s notes");
+        assert_eq!(parsed.text, "after:9223372036854775808s notes");
+    }
+
+    #[test]
+    fn test_unparsable_filters_stay_text() {
+        let parsed = parse_query("after:soon ext: note:x - C-3PO");
+        assert_eq!(parsed.text, "after:soon ext: note:x - C-3PO");
+        assert_eq!(parsed.filters, SearchFilters::default());
+    }
+}
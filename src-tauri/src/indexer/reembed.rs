@@ -0,0 +1,284 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use arrow_array::{RecordBatch, StringArray};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::database::CreateTableMode;
+use lancedb::query::{ExecutableQuery, QueryBase, Select};
+use lancedb::Table;
+use tokio::sync::Mutex;
+
+use super::embedding::ModelIdentity;
+use super::{db, embed_batch, get_model_dim, get_model_identity, maintenance, schema};
+use crate::config::VectorConfig;
+use crate::jobs::JobControl;
+use crate::state::ModelState;
+
+/// Chunks embedded per model call, and between two pause/cancel checks.
+const REEMBED_BATCH_SIZE: usize = 256;
+
+/// Where a table is rebuilt before the swap. Container tables all start with `c_`,
+/// so this never collides with one.
+pub fn shadow_table_name(table_name: &str) -> String {
+    format!("shadow_{}", table_name)
+}
+
+/// Every column but `vector`: the text and metadata are copied, only the vector is recomputed.
+fn copied_columns() -> Select {
+    let columns = ["path", "chunk_hash", "content", "content_hash"]
+        .into_iter()
+        .chain(db::META_COLUMNS.iter().copied());
+    Select::Columns(columns.map(|c| c.to_string()).collect())
+}
+
+fn strings<'a>(batch: &'a RecordBatch, name: &str) -> Result<&'a StringArray> {
+    batch
+        .column_by_name(name)
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| anyhow::anyhow!("column {} missing", name))
+}
+
+async fn drop_shadow(db: &Connection, shadow_name: &str) {
+    let _ = db.drop_table(shadow_name, &[]).await;
+    if let Err(e) = schema::clear_meta(db, shadow_name).await {
+        eprintln!("reembed: {}: {}", shadow_name, e);
+    }
+}
+
+/// Re-embeds every chunk of `table_name` with the current model into a shadow table,
+/// then swaps it in. The live table keeps serving queries until the swap; cancelling
+/// the job drops the shadow and leaves it untouched. The shadow is created with the
+/// container's current vector settings. Returns the number of chunks.
+pub async fn reembed_table<F>(
+    db: &Connection,
+    table_name: &str,
+    model_state: &Arc<Mutex<ModelState>>,
+    vectors: &VectorConfig,
+    job: &JobControl,
+    progress_callback: F,
+) -> Result<usize>
+where
+    F: Fn(usize, usize),
+{
+    let live = db.open_table(table_name).execute().await?;
+    let total = live.count_rows(None).await?;
+
+    let shadow_name = shadow_table_name(table_name);
+    // Left over by a run the app did not survive
+    drop_shadow(db, &shadow_name).await;
+    let dim = get_model_dim(model_state).await?;
+    let model = get_model_identity(model_state).await;
+    let shadow = db::get_or_create_table(db, &shadow_name, dim, &model, vectors).await?;
+
+    let result = fill_shadow(&live, &shadow, model_state, job, total, &progress_callback).await;
+    if let Err(e) = result {
+        drop_shadow(db, &shadow_name).await;
+        return Err(e);
+    }
+
+    swap(db, table_name, &shadow, &model, vectors).await?;
+    drop_shadow(db, &shadow_name).await;
+    Ok(total)
+}
+
+async fn fill_shadow(
+    live: &Table,
+    shadow: &Table,
+    model_state: &Arc<Mutex<ModelState>>,
+    job: &JobControl,
+    total: usize,
+    progress_callback: &impl Fn(usize, usize),
+) -> Result<()> {
+    let mut stream = live.query().select(copied_columns()).execute().await?;
+    let indexed_at = chrono::Utc::now().timestamp();
+    let mut done = 0;
+    progress_callback(done, total);
+
+    while let Some(batch) = stream.try_next().await? {
+        let mut start = 0;
+        while start < batch.num_rows() {
+            job.checkpoint().await?;
+            let slice = batch.slice(start, REEMBED_BATCH_SIZE.min(batch.num_rows() - start));
+            start += slice.num_rows();
+
+            let contents = strings(&slice, "content")?;
+            let texts: Vec<String> = (0..slice.num_rows())
+                .map(|i| contents.value(i).to_string())
+                .collect();
+            let vectors = embed_batch(model_state, texts.clone()).await?;
+
+            let paths = strings(&slice, "path")?;
+            let chunk_hashes = strings(&slice, "chunk_hash")?;
+            let content_hashes = strings(&slice, "content_hash")?;
+            let records: Vec<db::Record> = db::chunk_metas(&slice)
+                .into_iter()
+                .zip(texts)
+                .zip(vectors)
+                .enumerate()
+                .map(|(i, ((meta, content), vector))| db::Record {
+                    path: paths.value(i).to_string(),
+                    chunk_hash: chunk_hashes.value(i).to_string(),
+                    content,
+                    vector,
+                    content_hash: content_hashes.value(i).to_string(),
+                    meta: db::ChunkMeta { indexed_at, ..meta },
+                })
+                .collect();
+            db::append_records(shadow, records).await?;
+
+            done += slice.num_rows();
+            progress_callback(done, total);
+        }
+    }
+    Ok(())
+}
+
+/// Overwrites the live table with the shadow's rows in a single commit, so a reader
+/// sees either every old vector or every new one, never a mix. The new model is
+/// marked pending before the overwrite, so readers pick it up with the new rows
+/// rather than after. The indexes the overwrite discarded are rebuilt last;
+/// meanwhile searches run without them.
+async fn swap(
+    db: &Connection,
+    table_name: &str,
+    shadow: &Table,
+    model: &ModelIdentity,
+    vectors: &VectorConfig,
+) -> Result<()> {
+    // Streamed straight from the shadow, the table is never held in memory
+    let rows = shadow.query().execute().await?;
+    schema::begin_swap(db, table_name, model).await?;
+    let overwritten = db
+        .create_table_streaming(table_name, rows)
+        .mode(CreateTableMode::Overwrite)
+        .execute()
+        .await;
+    schema::settle_swap(db, table_name).await?;
+    let table = overwritten?;
+
+    schema::set_version(db, table_name, schema::SCHEMA_VERSION).await?;
+    maintenance::refresh_indexes(&table, vectors).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::jobs::{JobKind, JobManager};
+
+    fn identity(name: &str) -> ModelIdentity {
+        ModelIdentity {
+            name: name.to_string(),
+            revision: format!("{}/model.onnx", name),
+            passage_prefix: String::new(),
+            query_prefix: String::new(),
+        }
+    }
+
+    async fn connect(name: &str) -> Connection {
+        let dir = std::env::temp_dir().join(format!("recall_lite_reembed_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        lancedb::connect(dir.to_str().unwrap())
+            .execute()
+            .await
+            .unwrap()
+    }
+
+    async fn table_with(db: &Connection, name: &str, model: &ModelIdentity, rows: usize) -> Table {
+        let table = db::get_or_create_table(db, name, 4, model, &VectorConfig::default())
+            .await
+            .unwrap();
+        let records = (0..rows)
+            .map(|i| db::Record {
+                path: format!("@a1/file{}.txt", i),
+                chunk_hash: format!("chunk{}", i),
+                content: format!("content {}", i),
+                vector: vec![i as f32; 4],
+                content_hash: format!("hash{}", i),
+                meta: db::ChunkMeta::default(),
+            })
+            .collect();
+        db::append_records(&table, records).await.unwrap();
+        table
+    }
+
+    #[tokio::test]
+    async fn test_cancel_leaves_live_table_untouched() {
+        let db = connect("cancel").await;
+        let old = identity("Old");
+        let live = table_with(&db, "c_work", &old, 3).await;
+
+        // The dimension is cached, so no model has to be loaded
+        let model_state = Arc::new(Mutex::new(ModelState {
+            model: None,
+            init_error: None,
+            cached_dim: Some(4),
+            identity: identity("New"),
+            legacy: None,
+            cache_dir: std::env::temp_dir(),
+        }));
+        let (tx, _rx) = crate::events::channel();
+        let checkpoint_dir = std::env::temp_dir().join("recall_lite_reembed_jobs");
+        let _ = std::fs::remove_dir_all(&checkpoint_dir);
+        let jobs = JobManager::new(tx, checkpoint_dir);
+        let (id, job) = jobs
+            .start(JobKind::ReembedModel, "Work", &[])
+            .await
+            .unwrap();
+        jobs.cancel(id).await.unwrap();
+
+        let vectors = VectorConfig::default();
+        let result = reembed_table(&db, "c_work", &model_state, &vectors, &job, |_, _| {}).await;
+        assert!(result
+            .unwrap_err()
+            .downcast_ref::<crate::jobs::JobCancelled>()
+            .is_some());
+        let tables = db.table_names().execute().await.unwrap();
+        assert!(!tables.contains(&shadow_table_name("c_work")));
+        assert_eq!(live.count_rows(None).await.unwrap(), 3);
+        assert_eq!(schema::get_model(&db, "c_work").await.unwrap(), Some(old));
+    }
+
+    #[tokio::test]
+    async fn test_swap_records_model_with_new_rows() {
+        let db = connect("swap").await;
+        let new = identity("New");
+        table_with(&db, "c_work", &identity("Old"), 3).await;
+        let shadow = table_with(&db, &shadow_table_name("c_work"), &new, 2).await;
+
+        swap(&db, "c_work", &shadow, &new, &VectorConfig::default())
+            .await
+            .unwrap();
+        let live = db.open_table("c_work").execute().await.unwrap();
+        assert_eq!(live.count_rows(None).await.unwrap(), 2);
+        assert_eq!(schema::get_model(&db, "c_work").await.unwrap(), Some(new));
+    }
+
+    #[tokio::test]
+    async fn test_pending_swap_follows_table_version() {
+        let db = connect("pending").await;
+        let old = identity("Old");
+        let new = identity("New");
+        let live = table_with(&db, "c_work", &old, 1).await;
+
+        // Marked but not overwritten yet: readers still get the old model
+        schema::begin_swap(&db, "c_work", &new).await.unwrap();
+        assert_eq!(
+            schema::get_model(&db, "c_work").await.unwrap(),
+            Some(old.clone())
+        );
+        schema::settle_swap(&db, "c_work").await.unwrap();
+        assert_eq!(schema::get_model(&db, "c_work").await.unwrap(), Some(old));
+
+        // The overwrite committed, then the app died before recording the model
+        schema::begin_swap(&db, "c_work", &new).await.unwrap();
+        live.delete("true").await.unwrap();
+        assert_eq!(
+            schema::get_model(&db, "c_work").await.unwrap(),
+            Some(new.clone())
+        );
+        schema::settle_swap(&db, "c_work").await.unwrap();
+        assert_eq!(schema::get_model(&db, "c_work").await.unwrap(), Some(new));
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use serde::{Deserialize, Deserializer, Serialize};
+
+use super::ChunkStats;
+use crate::config::get_table_name;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileIssue {
+    pub path: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Timings {
+    /// Walk and text extraction, which run side by side on the walker threads.
+    pub walk_ms: u64,
+    pub embed_ms: u64,
+    /// ANN, FTS and chunk hash index builds.
+    pub index_ms: u64,
+    pub total_ms: u64,
+}
+
+impl std::ops::AddAssign for Timings {
+    fn add_assign(&mut self, other: Self) {
+        self.walk_ms += other.walk_ms;
+        self.embed_ms += other.embed_ms;
+        self.index_ms += other.index_ms;
+        self.total_ms += other.total_ms;
+    }
+}
+
+/// Indexed paths a report lists; the rest are only counted.
+pub const INDEXED_SAMPLE: usize = 20;
+
+/// Skipped files, and failed ones, a report lists; the rest are only counted.
+pub const LISTED_ISSUES: usize = 200;
+
+/// Appends `more` to `listed` up to [`LISTED_ISSUES`], counting the overflow in `unlisted`.
+pub fn list_issues(listed: &mut Vec<FileIssue>, unlisted: &mut usize, more: Vec<FileIssue>) {
+    let room = LISTED_ISSUES.saturating_sub(listed.len());
+    *unlisted += more.len().saturating_sub(room);
+    listed.extend(more.into_iter().take(room));
+}
+
+/// Older reports listed every indexed path.
+fn count_or_list<'de, D>(deserializer: D) -> Result<usize, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Indexed {
+        Count(usize),
+        List(Vec<String>),
+    }
+    Ok(match Indexed::deserialize(deserializer)? {
+        Indexed::Count(count) => count,
+        Indexed::List(paths) => paths.len(),
+    })
+}
+
+/// What one indexing run did with every file it walked.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct IndexReport {
+    #[serde(default, deserialize_with = "count_or_list")]
+    pub indexed: usize,
+    /// The first [`INDEXED_SAMPLE`] files indexed.
+    #[serde(default)]
+    pub indexed_sample: Vec<String>,
+    pub unchanged: usize,
+    /// Files of a type that is not indexed. Counted, not listed.
+    pub unsupported: usize,
+    /// The first [`LISTED_ISSUES`] files left out on purpose (size, encoding, excluded extension).
+    pub skipped: Vec<FileIssue>,
+    /// Skipped files past the listed ones.
+    #[serde(default)]
+    pub skipped_unlisted: usize,
+    /// The first [`LISTED_ISSUES`] files that could not be read.
+    pub failed: Vec<FileIssue>,
+    /// Failed files past the listed ones.
+    #[serde(default)]
+    pub failed_unlisted: usize,
+    pub pruned: usize,
+    pub chunks: ChunkStats,
+    pub timings: Timings,
+    /// Unix seconds.
+    #[serde(default)]
+    pub finished_at: i64,
+}
+
+impl std::ops::AddAssign for IndexReport {
+    fn add_assign(&mut self, other: Self) {
+        self.indexed += other.indexed;
+        self.indexed_sample.extend(other.indexed_sample);
+        self.indexed_sample.truncate(INDEXED_SAMPLE);
+        self.unchanged += other.unchanged;
+        self.unsupported += other.unsupported;
+        self.skipped_unlisted += other.skipped_unlisted;
+        list_issues(&mut self.skipped, &mut self.skipped_unlisted, other.skipped);
+        self.failed_unlisted += other.failed_unlisted;
+        list_issues(&mut self.failed, &mut self.failed_unlisted, other.failed);
+        self.pruned += other.pruned;
+        self.chunks += other.chunks;
+        self.timings += other.timings;
+        self.finished_at = self.finished_at.max(other.finished_at);
+    }
+}
+
+impl IndexReport {
+    pub fn skipped_count(&self) -> usize {
+        self.skipped.len() + self.skipped_unlisted
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.failed.len() + self.failed_unlisted
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "{} indexed, {} unchanged, {} skipped, {} failed, {} removed, {} chunks reused",
+            self.indexed,
+            self.unchanged,
+            self.skipped_count(),
+            self.failed_count(),
+            self.pruned,
+            self.chunks.reused
+        )
+    }
+
+    /// Keeps the last run of each container next to the config, where the MCP server can read it.
+    pub fn save(&self, dir: &Path, container: &str) -> Result<()> {
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(report_path(dir, container), serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub fn load(dir: &Path, container: &str) -> Option<Self> {
+        let content = std::fs::read_to_string(report_path(dir, container)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+}
+
+/// Where reports live, derived from the config file location shared by the GUI and MCP.
+pub fn reports_dir(config_path: &Path) -> PathBuf {
+    config_path.with_file_name("reports")
+}
+
+fn report_path(dir: &Path, container: &str) -> PathBuf {
+    dir.join(format!("{}.json", get_table_name(container)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(count: usize) -> Vec<FileIssue> {
+        (0..count)
+            .map(|n| FileIssue {
+                path: format!("@a1/{}.bin", n),
+                reason: "not valid UTF-8".to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_merged_reports_cap_listed_issues() {
+        let mut total = IndexReport {
+            skipped: issues(LISTED_ISSUES - 1),
+            ..IndexReport::default()
+        };
+        total += IndexReport {
+            skipped: issues(3),
+            skipped_unlisted: 10,
+            failed: issues(2),
+            ..IndexReport::default()
+        };
+        assert_eq!(total.skipped.len(), LISTED_ISSUES);
+        assert_eq!(total.skipped_unlisted, 12);
+        assert_eq!(total.skipped_count(), LISTED_ISSUES + 12);
+        assert_eq!(total.failed_count(), 2);
+    }
+}
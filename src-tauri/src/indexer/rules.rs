@@ -0,0 +1,111 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use ignore::overrides::{Override, OverrideBuilder};
+
+/// Compiles a container's globs against one indexed root.
+///
+/// Same semantics as `rg --glob`: a plain glob whitelists (once any is present, files
+/// matching none of them are skipped, directories are still walked), a `!glob` excludes.
+pub fn build_override(root: &str, globs: &[String]) -> Result<Override> {
+    if globs.is_empty() {
+        return Ok(Override::empty());
+    }
+    let mut builder = OverrideBuilder::new(root);
+    builder.case_insensitive(cfg!(windows))?;
+    for glob in globs {
+        builder
+            .add(glob)
+            .map_err(|e| anyhow!("Invalid glob '{}': {}", glob, e))?;
+    }
+    Ok(builder.build()?)
+}
+
+/// Glob rules of a container across all of its roots, for matching paths outside a walk.
+pub struct GlobRules {
+    roots: Vec<(PathBuf, Override)>,
+}
+
+impl GlobRules {
+    pub fn build(roots: &[String], globs: &[String]) -> Result<Self> {
+        let roots = roots
+            .iter()
+            .map(|root| Ok((PathBuf::from(root), build_override(root, globs)?)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self { roots })
+    }
+
+    /// Whether the walker would have skipped `path`: the path itself or one of its parent
+    /// directories below the root is excluded. Paths outside every root are never excluded.
+    pub fn is_excluded(&self, path: &Path, is_dir: bool) -> bool {
+        let Some((root, rules)) = self
+            .roots
+            .iter()
+            .filter(|(root, _)| path.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+        else {
+            return false;
+        };
+        if rules.is_empty() {
+            return false;
+        }
+        if rules.matched(path, is_dir).is_ignore() {
+            return true;
+        }
+        path.ancestors()
+            .skip(1)
+            .take_while(|dir| *dir != root.as_path())
+            .any(|dir| rules.matched(dir, true).is_ignore())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(globs: &[&str]) -> GlobRules {
+        let globs: Vec<String> = globs.iter().map(|g| g.to_string()).collect();
+        GlobRules::build(&["/repo".to_string()], &globs).unwrap()
+    }
+
+    #[test]
+    fn test_no_globs_excludes_nothing() {
+        let r = rules(&[]);
+        assert!(!r.is_excluded(Path::new("/repo/src/main.rs"), false));
+    }
+
+    #[test]
+    fn test_negated_glob_excludes() {
+        let r = rules(&["!**/node_modules/**", "!**/*.generated.rs"]);
+        assert!(r.is_excluded(Path::new("/repo/web/node_modules/react/index.js"), false));
+        assert!(r.is_excluded(Path::new("/repo/src/api.generated.rs"), false));
+        assert!(!r.is_excluded(Path::new("/repo/src/api.rs"), false));
+    }
+
+    #[test]
+    fn test_plain_glob_whitelists_files_only() {
+        let r = rules(&["docs/**/*.md"]);
+        assert!(!r.is_excluded(Path::new("/repo/docs/guide/intro.md"), false));
+        assert!(r.is_excluded(Path::new("/repo/src/main.rs"), false));
+        assert!(!r.is_excluded(Path::new("/repo/src"), true));
+    }
+
+    #[test]
+    fn test_excluded_parent_directory() {
+        let r = rules(&["!target/"]);
+        assert!(r.is_excluded(Path::new("/repo/target/debug/build.log"), false));
+        assert!(!r.is_excluded(Path::new("/repo/src/target.rs"), false));
+    }
+
+    #[test]
+    fn test_paths_outside_roots_are_kept() {
+        let r = rules(&["docs/**/*.md"]);
+        assert!(!r.is_excluded(Path::new("/elsewhere/main.rs"), false));
+    }
+
+    #[test]
+    fn test_invalid_glob_is_rejected() {
+        let globs = vec!["src/[".to_string()];
+        assert!(GlobRules::build(&["/repo".to_string()], &globs).is_err());
+    }
+}
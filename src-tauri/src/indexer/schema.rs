@@ -0,0 +1,382 @@
+use std::collections::HashSet;
+use std::sync::{Arc, OnceLock};
+
+use anyhow::{anyhow, Result};
+use arrow_array::{RecordBatch, RecordBatchIterator, StringArray};
+use arrow_schema::{DataType, Field, Schema};
+use futures::TryStreamExt;
+use lancedb::connection::Connection;
+use lancedb::query::{ExecutableQuery, QueryBase};
+use lancedb::table::NewColumnTransform;
+use lancedb::Table;
+use serde::{Deserialize, Serialize};
+
+use super::embedding::ModelIdentity;
+use super::filter::Filter;
+
+/// Version of the container table layout written by this build.
+pub const SCHEMA_VERSION: i32 = 3;
+
+/// Key/value rows about each container table, kept in LanceDB so the MCP server sees them too.
+const META_TABLE: &str = "recall_meta";
+const VERSION_KEY: &str = "schema_version";
+const MODEL_KEY: &str = "embedding_model";
+const SWAP_KEY: &str = "pending_swap";
+
+/// Columns added in one schema version, each with the SQL expression that fills existing rows.
+struct Migration {
+    to: i32,
+    columns: &'static [(&'static str, &'static str)],
+}
+
+/// Every layout change since the first release, oldest first. Append only.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        to: 1,
+        // 0 never matches a real mtime, so every file is re-read once
+        columns: &[("mtime", "CAST(0 AS BIGINT)")],
+    },
+    Migration {
+        to: 2,
+        // chunk_index -1 keeps legacy rows out of the (path, chunk_index) merge key;
+        // they are replaced as a whole the next time their file changes.
+        columns: &[
+            ("chunk_index", "CAST(-1 AS INT)"),
+            ("chunk_hash", "''"),
+            ("content_hash", "''"),
+        ],
+    },
+    Migration {
+        to: 3,
+        columns: &[
+            ("start_byte", "CAST(0 AS BIGINT)"),
+            ("end_byte", "CAST(0 AS BIGINT)"),
+            ("start_line", "CAST(0 AS INT)"),
+            ("end_line", "CAST(0 AS INT)"),
+            ("file_size", "CAST(0 AS BIGINT)"),
+            ("extension", "''"),
+            ("indexed_at", "CAST(0 AS BIGINT)"),
+        ],
+    },
+];
+
+/// The table can't be brought up to date in place. Nothing is dropped until the user
+/// confirms, the GUI is told through `AppEvent::RebuildRequired`.
+#[derive(Debug)]
+pub struct RebuildRequired {
+    pub table_name: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for RebuildRequired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Index '{}' needs a full rebuild: {}", self.table_name, self.reason)
+    }
+}
+
+impl std::error::Error for RebuildRequired {}
+
+/// Why `error` calls for a rebuild, if it does.
+pub fn rebuild_reason(error: &anyhow::Error) -> Option<String> {
+    error
+        .downcast_ref::<RebuildRequired>()
+        .map(|e| e.reason.clone())
+}
+
+/// Version implied by the columns present, for tables created before versions were stored.
+fn detect_version(schema: &Schema) -> i32 {
+    MIGRATIONS
+        .iter()
+        .take_while(|m| {
+            m.columns
+                .iter()
+                .all(|(name, _)| schema.field_with_name(name).is_ok())
+        })
+        .last()
+        .map_or(0, |m| m.to)
+}
+
+/// Tables already checked by this process, so the watcher's per-file calls stay cheap.
+fn checked() -> &'static std::sync::Mutex<HashSet<String>> {
+    static CHECKED: OnceLock<std::sync::Mutex<HashSet<String>>> = OnceLock::new();
+    CHECKED.get_or_init(Default::default)
+}
+
+/// Held while a table migrates, so two searches opening the same old table don't both
+/// add its columns.
+fn migrating() -> &'static tokio::sync::Mutex<()> {
+    static MIGRATING: OnceLock<tokio::sync::Mutex<()>> = OnceLock::new();
+    MIGRATING.get_or_init(Default::default)
+}
+
+/// Applies every migration newer than the table's version, adding columns with defaults.
+/// Returns the versions applied.
+pub async fn migrate(db: &Connection, table: &Table, table_name: &str) -> Result<Vec<i32>> {
+    let is_checked = || checked().lock().unwrap_or_else(|e| e.into_inner()).contains(table_name);
+    if is_checked() {
+        return Ok(Vec::new());
+    }
+    let _migrating = migrating().lock().await;
+    if is_checked() {
+        return Ok(Vec::new());
+    }
+
+    let schema = table.schema().await?;
+    let stored = get_meta(db, table_name, VERSION_KEY)
+        .await?
+        .and_then(|v| v.parse::<i32>().ok());
+    let current = stored.unwrap_or_else(|| detect_version(&schema));
+    if current > SCHEMA_VERSION {
+        return Err(anyhow!(
+            "Index '{}' was written by a newer version of Recall-Lite (schema {}, this build reads up to {})",
+            table_name,
+            current,
+            SCHEMA_VERSION
+        ));
+    }
+
+    let mut applied = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|m| m.to > current) {
+        // Skips columns a half-finished earlier attempt already added
+        let missing: Vec<(String, String)> = migration
+            .columns
+            .iter()
+            .filter(|(name, _)| schema.field_with_name(name).is_err())
+            .map(|(name, expr)| (name.to_string(), expr.to_string()))
+            .collect();
+        if !missing.is_empty() {
+            table
+                .add_columns(NewColumnTransform::SqlExpressions(missing), None)
+                .await?;
+        }
+        set_version(db, table_name, migration.to).await?;
+        applied.push(migration.to);
+    }
+    if stored.is_none() && applied.is_empty() {
+        set_version(db, table_name, current).await?;
+    }
+
+    checked()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(table_name.to_string());
+    Ok(applied)
+}
+
+pub async fn set_version(db: &Connection, table_name: &str, version: i32) -> Result<()> {
+    set_meta(db, table_name, VERSION_KEY, &version.to_string()).await
+}
+
+/// A re-embed about to overwrite a table: the model of the new vectors, and the
+/// table version they replace. Any later version holds the new vectors, even when
+/// the run died before recording their model.
+#[derive(Serialize, Deserialize)]
+struct PendingSwap {
+    model: ModelIdentity,
+    from_version: u64,
+}
+
+async fn pending_swap(db: &Connection, table_name: &str) -> Result<Option<PendingSwap>> {
+    Ok(get_meta(db, table_name, SWAP_KEY)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// The new model once a pending swap's overwrite has committed, `None` while the
+/// table still holds the old vectors.
+async fn swapped_model(db: &Connection, table_name: &str) -> Result<Option<ModelIdentity>> {
+    let Some(swap) = pending_swap(db, table_name).await? else {
+        return Ok(None);
+    };
+    let Ok(table) = db.open_table(table_name).execute().await else {
+        return Ok(None);
+    };
+    Ok((table.version().await? > swap.from_version).then_some(swap.model))
+}
+
+/// The model the table's vectors came from, if recorded. Mid-swap, this follows
+/// the table version, so a search never embeds its query for the other model.
+pub async fn get_model(db: &Connection, table_name: &str) -> Result<Option<ModelIdentity>> {
+    if let Some(model) = swapped_model(db, table_name).await? {
+        return Ok(Some(model));
+    }
+    Ok(get_meta(db, table_name, MODEL_KEY)
+        .await?
+        .and_then(|json| serde_json::from_str(&json).ok()))
+}
+
+/// Records that `table_name` is about to be overwritten with vectors from `model`.
+/// Nothing else may write to the table until [`settle_swap`].
+pub async fn begin_swap(db: &Connection, table_name: &str, model: &ModelIdentity) -> Result<()> {
+    let table = db.open_table(table_name).execute().await?;
+    let swap = PendingSwap {
+        model: model.clone(),
+        from_version: table.version().await?,
+    };
+    set_meta(db, table_name, SWAP_KEY, &serde_json::to_string(&swap)?).await
+}
+
+/// Ends a swap: records the new model if the overwrite committed, keeps the old one
+/// otherwise. Run at startup for swaps the app did not survive.
+pub async fn settle_swap(db: &Connection, table_name: &str) -> Result<()> {
+    if pending_swap(db, table_name).await?.is_none() {
+        return Ok(());
+    }
+    if let Some(model) = swapped_model(db, table_name).await? {
+        set_model(db, table_name, &model).await?;
+    }
+    delete_meta(db, table_name, SWAP_KEY).await
+}
+
+pub async fn set_model(db: &Connection, table_name: &str, model: &ModelIdentity) -> Result<()> {
+    set_meta(db, table_name, MODEL_KEY, &serde_json::to_string(model)?).await
+}
+
+/// The recorded model, when it isn't `current`.
+pub async fn model_mismatch(
+    db: &Connection,
+    table_name: &str,
+    current: &ModelIdentity,
+) -> Result<Option<ModelIdentity>> {
+    Ok(get_model(db, table_name)
+        .await?
+        .filter(|indexed| indexed != current))
+}
+
+pub fn mismatch_error(indexed: &ModelIdentity, current: &ModelIdentity) -> anyhow::Error {
+    anyhow!(
+        "Model changed: this index was built with {} but the current model is {}. Please rebuild the index.",
+        indexed.describe(),
+        current.describe()
+    )
+}
+
+fn meta_schema() -> Arc<Schema> {
+    Arc::new(Schema::new(vec![
+        Field::new("table_name", DataType::Utf8, false),
+        Field::new("key", DataType::Utf8, false),
+        Field::new("value", DataType::Utf8, false),
+    ]))
+}
+
+async fn meta_table(db: &Connection) -> Result<Table> {
+    if let Ok(table) = db.open_table(META_TABLE).execute().await {
+        return Ok(table);
+    }
+    let schema = meta_schema();
+    Ok(db
+        .create_table(META_TABLE, RecordBatchIterator::new(vec![], schema))
+        .execute()
+        .await?)
+}
+
+fn meta_filter(table_name: &str) -> Filter {
+    Filter::Eq("table_name", table_name.into())
+}
+
+pub async fn get_meta(db: &Connection, table_name: &str, key: &str) -> Result<Option<String>> {
+    let Ok(table) = db.open_table(META_TABLE).execute().await else {
+        return Ok(None);
+    };
+    let results = table
+        .query()
+        .only_if(
+            Filter::And(vec![meta_filter(table_name), Filter::Eq("key", key.into())]).to_sql(),
+        )
+        .limit(1)
+        .execute()
+        .await?
+        .try_collect::<Vec<_>>()
+        .await?;
+
+    Ok(results.iter().find_map(|batch| {
+        batch
+            .column_by_name("value")
+            .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+            .filter(|values| !values.is_empty())
+            .map(|values| values.value(0).to_string())
+    }))
+}
+
+pub async fn set_meta(db: &Connection, table_name: &str, key: &str, value: &str) -> Result<()> {
+    let table = meta_table(db).await?;
+    let schema = meta_schema();
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from(vec![table_name])),
+            Arc::new(StringArray::from(vec![key])),
+            Arc::new(StringArray::from(vec![value])),
+        ],
+    )?;
+    let mut merge = table.merge_insert(&["table_name", "key"]);
+    merge.when_matched_update_all(None).when_not_matched_insert_all();
+    merge
+        .execute(Box::new(RecordBatchIterator::new(vec![Ok(batch)], schema)))
+        .await?;
+    Ok(())
+}
+
+async fn delete_meta(db: &Connection, table_name: &str, key: &str) -> Result<()> {
+    if let Ok(table) = db.open_table(META_TABLE).execute().await {
+        let filter = Filter::And(vec![meta_filter(table_name), Filter::Eq("key", key.into())]);
+        table.delete(&filter.to_sql()).await?;
+    }
+    Ok(())
+}
+
+/// Forgets everything about a table that is being dropped.
+pub async fn clear_meta(db: &Connection, table_name: &str) -> Result<()> {
+    checked()
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .remove(table_name);
+    if let Ok(table) = db.open_table(META_TABLE).execute().await {
+        table.delete(&meta_filter(table_name).to_sql()).await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema_with(columns: &[&str]) -> Schema {
+        Schema::new(
+            columns
+                .iter()
+                .map(|name| Field::new(*name, DataType::Utf8, false))
+                .collect::<Vec<_>>(),
+        )
+    }
+
+    #[test]
+    fn test_detect_version_of_legacy_tables() {
+        assert_eq!(detect_version(&schema_with(&["path", "content", "vector"])), 0);
+        assert_eq!(detect_version(&schema_with(&["path", "content", "vector", "mtime"])), 1);
+        assert_eq!(
+            detect_version(&schema_with(&[
+                "path",
+                "content",
+                "vector",
+                "mtime",
+                "chunk_index",
+                "chunk_hash",
+                "content_hash",
+            ])),
+            2
+        );
+    }
+
+    #[test]
+    fn test_migrations_are_ordered_and_end_at_current() {
+        assert!(MIGRATIONS.windows(2).all(|w| w[0].to < w[1].to));
+        assert_eq!(MIGRATIONS.last().map(|m| m.to), Some(SCHEMA_VERSION));
+    }
+
+    #[test]
+    fn test_current_schema_detects_as_current() {
+        let schema = super::super::db::make_schema(8, crate::config::VectorPrecision::Float32);
+        assert_eq!(detect_version(&schema), SCHEMA_VERSION);
+    }
+}
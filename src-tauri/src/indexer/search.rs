@@ -1,51 +1,134 @@
 use std::collections::HashMap;
 
 use anyhow::{anyhow, Result};
-use arrow_array::{Float32Array, StringArray};
+use arrow_array::{Float32Array, RecordBatch, StringArray};
 use futures::TryStreamExt;
 use lancedb::connection::Connection;
 use lancedb::index::scalar::FullTextSearchQuery;
 use lancedb::query::{ExecutableQuery, QueryBase};
 use lancedb::DistanceType;
+use serde::Serialize;
+
+use super::db::{self, ChunkMeta};
+use super::filter::Filter;
+use super::query::TimeWindow;
+use super::schema;
+use crate::config::{FusionConfig, FusionStrategy, VectorConfig};
+
+/// One matching chunk. `score` is a cosine distance out of [`search_files`], BM25 out of
+/// [`search_fts`], then an RRF or reranker score further down the pipeline.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SearchHit {
+    /// Set by [`search_pipeline`], empty out of the single-table searches.
+    pub container: String,
+    pub path: String,
+    pub content: String,
+    pub score: f32,
+    pub meta: ChunkMeta,
+}
 
-pub fn build_filter_expr(
-    path_prefix: Option<&str>,
-    file_extensions: Option<&[String]>,
-) -> Option<String> {
-    let mut clauses = Vec::new();
-
-    if let Some(prefix) = path_prefix {
-        let escaped = prefix
-            .replace('\\', "\\\\")
-            .replace('\'', "''")
-            .replace('%', "\\%")
-            .replace('_', "\\_");
-        clauses.push(format!("path LIKE '{}%' ESCAPE '\\'", escaped));
-    }
-
-    if let Some(exts) = file_extensions {
-        if !exts.is_empty() {
-            let ext_clauses: Vec<String> = exts
-                .iter()
-                .map(|ext| {
-                    let clean = ext
-                        .trim_start_matches('.')
-                        .replace('\\', "\\\\")
-                        .replace('\'', "''")
-                        .replace('%', "\\%")
-                        .replace('_', "\\_");
-                    format!("path LIKE '%.{}' ESCAPE '\\'", clean)
-                })
-                .collect();
-            clauses.push(format!("({})", ext_clauses.join(" OR ")));
+fn hit_columns() -> lancedb::query::Select {
+    let columns = ["path", "content"].iter().chain(db::META_COLUMNS);
+    lancedb::query::Select::Columns(columns.map(|c| c.to_string()).collect())
+}
+
+fn hits_from_batch(batch: &RecordBatch, score_column: &str) -> Result<Vec<SearchHit>> {
+    let paths = batch
+        .column_by_name("path")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| anyhow!("Missing or invalid 'path' column"))?;
+    let contents = batch
+        .column_by_name("content")
+        .and_then(|c| c.as_any().downcast_ref::<StringArray>())
+        .ok_or_else(|| anyhow!("Missing or invalid 'content' column"))?;
+    let scores = batch
+        .column_by_name(score_column)
+        .and_then(|c| c.as_any().downcast_ref::<Float32Array>());
+
+    Ok(db::chunk_metas(batch)
+        .into_iter()
+        .enumerate()
+        .map(|(i, meta)| SearchHit {
+            container: String::new(),
+            path: paths.value(i).to_string(),
+            content: contents.value(i).to_string(),
+            score: scores.map_or(0.0, |s| s.value(i)),
+            meta,
+        })
+        .collect())
+}
+
+/// Chunks fetched per file wanted when a search keeps one chunk per file.
+const CHUNKS_PER_FILE: usize = 2;
+
+/// BM25 hits fetched for each variant of the query.
+const FTS_VARIANT_LIMIT: usize = 30;
+
+/// Candidates fetched per result when hits are also filtered on their text, since some
+/// of them will be dropped after the query.
+const TEXT_FILTER_OVERFETCH: usize = 4;
+
+/// What a search is restricted to besides the query. Paths are stored paths.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchFilters {
+    /// Paths under any of these.
+    pub path_prefixes: Vec<String>,
+    pub extensions: Vec<String>,
+    pub modified: Option<TimeWindow>,
+    /// Text a chunk must contain, case-insensitive. Checked on the hits, not in the query.
+    pub phrases: Vec<String>,
+    /// Text a chunk must not contain, same rules as `phrases`.
+    pub excluded: Vec<String>,
+}
+
+impl SearchFilters {
+    pub fn to_filter(&self) -> Option<Filter> {
+        let mut filters = Vec::new();
+        if !self.path_prefixes.is_empty() {
+            filters.push(Filter::path_under_any(&self.path_prefixes));
+        }
+        if !self.extensions.is_empty() {
+            filters.push(Filter::extension_in(&self.extensions));
         }
+        if let Some(window) = self.modified.filter(|w| !w.is_open()) {
+            filters.push(Filter::modified_between(window.after, window.before));
+        }
+        Filter::all(filters)
     }
 
-    if clauses.is_empty() {
-        None
-    } else {
-        Some(clauses.join(" AND "))
+    fn filters_text(&self) -> bool {
+        !self.phrases.is_empty() || !self.excluded.is_empty()
     }
+
+    fn keeps(&self, content: &str) -> bool {
+        if !self.filters_text() {
+            return true;
+        }
+        let content = content.to_lowercase();
+        self.phrases.iter().all(|p| content.contains(&p.to_lowercase()))
+            && !self.excluded.iter().any(|e| content.contains(&e.to_lowercase()))
+    }
+
+    /// Rows to ask the table for to end up with about `wanted` hits.
+    fn fetch_limit(&self, wanted: usize) -> usize {
+        if self.filters_text() {
+            wanted * TEXT_FILTER_OVERFETCH
+        } else {
+            wanted
+        }
+    }
+}
+
+pub fn build_filter_expr(
+    path_prefix: Option<&str>,
+    file_extensions: Option<&[String]>,
+) -> Option<String> {
+    let filters = SearchFilters {
+        path_prefixes: path_prefix.map(str::to_string).into_iter().collect(),
+        extensions: file_extensions.map(<[String]>::to_vec).unwrap_or_default(),
+        ..Default::default()
+    };
+    filters.to_filter().map(|f| f.to_sql())
 }
 
 pub async fn search_files(
@@ -53,10 +136,10 @@ pub async fn search_files(
     table_name: &str,
     query_vector: &[f32],
     limit: usize,
-    path_prefix: Option<&str>,
-    file_extensions: Option<&[String]>,
+    filters: &SearchFilters,
     multi_chunk: bool,
-) -> Result<Vec<(String, String, f32)>> {
+    vectors: &VectorConfig,
+) -> Result<Vec<SearchHit>> {
     let table = match db.open_table(table_name).execute().await {
         Ok(t) => t,
         Err(_) => return Err(anyhow!("No index found for '{}'. Index some folders first.", table_name)),
@@ -74,16 +157,22 @@ pub async fn search_files(
         }
     }
 
-    let search_limit = if multi_chunk { limit * 3 } else { limit * 2 };
+    let search_limit = filters.fetch_limit(if multi_chunk { limit * 3 } else { limit * 2 });
 
     let mut query = table
         .vector_search(query_vector)?
         .distance_type(DistanceType::Cosine)
-        .select(lancedb::query::Select::Columns(vec!["path".to_string(), "content".to_string()]))
+        .select(hit_columns())
         .limit(search_limit);
 
-    if let Some(filter) = build_filter_expr(path_prefix, file_extensions) {
-        query = query.only_if(filter);
+    if let Some(nprobes) = vectors.nprobes {
+        query = query.nprobes(nprobes);
+    }
+    if let Some(refine_factor) = vectors.refine_factor {
+        query = query.refine_factor(refine_factor);
+    }
+    if let Some(filter) = filters.to_filter() {
+        query = query.only_if(filter.to_sql());
     }
 
     let results = query
@@ -92,80 +181,31 @@ pub async fn search_files(
         .try_collect::<Vec<_>>()
         .await?;
 
-    if multi_chunk {
-        let mut matches = Vec::new();
-
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'path' column"))?;
-
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'content' column"))?;
-
-            let dist_array = batch
-                .column_by_name("_distance")
-                .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
-                .ok_or_else(|| anyhow!("Missing or invalid '_distance' column"))?;
-
-            for i in 0..batch.num_rows() {
-                matches.push((
-                    path_array.value(i).to_string(),
-                    content_array.value(i).to_string(),
-                    dist_array.value(i),
-                ));
-            }
-        }
+    let mut matches = Vec::new();
+    for batch in &results {
+        matches.extend(
+            hits_from_batch(batch, "_distance")?
+                .into_iter()
+                .filter(|hit| filters.keeps(&hit.content)),
+        );
+    }
 
-        matches.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
-        matches.truncate(limit);
-        Ok(matches)
-    } else {
-        let mut best_per_file: HashMap<String, (String, f32)> = HashMap::new();
-
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'path' column"))?;
-
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>())
-                .ok_or_else(|| anyhow!("Missing or invalid 'content' column"))?;
-
-            let dist_array = batch
-                .column_by_name("_distance")
-                .and_then(|c| c.as_any().downcast_ref::<Float32Array>())
-                .ok_or_else(|| anyhow!("Missing or invalid '_distance' column"))?;
-
-            for i in 0..batch.num_rows() {
-                let path = path_array.value(i).to_string();
-                let content = content_array.value(i).to_string();
-                let dist = dist_array.value(i);
-
-                match best_per_file.get(&path) {
-                    Some((_, existing_dist)) if *existing_dist <= dist => {}
-                    _ => {
-                        best_per_file.insert(path, (content, dist));
-                    }
+    if !multi_chunk {
+        let mut best_per_file: HashMap<String, SearchHit> = HashMap::new();
+        for hit in matches {
+            match best_per_file.get(&hit.path) {
+                Some(existing) if existing.score <= hit.score => {}
+                _ => {
+                    best_per_file.insert(hit.path.clone(), hit);
                 }
             }
         }
-
-        let mut matches: Vec<(String, String, f32)> = best_per_file
-            .into_iter()
-            .map(|(path, (content, dist))| (path, content, dist))
-            .collect();
-
-        matches.sort_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal));
-        matches.truncate(limit);
-
-        Ok(matches)
+        matches = best_per_file.into_values().collect();
     }
+
+    matches.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal));
+    matches.truncate(limit);
+    Ok(matches)
 }
 
 pub async fn search_fts(
@@ -173,24 +213,24 @@ pub async fn search_fts(
     table_name: &str,
     query: &str,
     limit: usize,
-    path_prefix: Option<&str>,
-    file_extensions: Option<&[String]>,
+    filters: &SearchFilters,
     multi_chunk: bool,
-) -> Result<Vec<(String, String)>> {
+) -> Result<Vec<SearchHit>> {
     let table = match db.open_table(table_name).execute().await {
         Ok(t) => t,
         Err(_) => return Err(anyhow!("No index found for '{}'. Index some folders first.", table_name)),
     };
 
     let fts_query = FullTextSearchQuery::new(query.to_string());
-    let search_limit = if multi_chunk { limit * 3 } else { limit * 2 };
+    let search_limit = filters.fetch_limit(if multi_chunk { limit * 3 } else { limit * 2 });
     let mut q = table
         .query()
         .full_text_search(fts_query)
+        .select(hit_columns())
         .limit(search_limit);
 
-    if let Some(filter) = build_filter_expr(path_prefix, file_extensions) {
-        q = q.only_if(filter);
+    if let Some(filter) = filters.to_filter() {
+        q = q.only_if(filter.to_sql());
     }
 
     let results = q
@@ -200,46 +240,18 @@ pub async fn search_fts(
         .await?;
 
     let mut matches = Vec::new();
+    let mut seen_paths = std::collections::HashSet::new();
 
-    if multi_chunk {
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-
-            if let (Some(paths), Some(contents)) = (path_array, content_array) {
-                for i in 0..batch.num_rows() {
-                    matches.push((paths.value(i).to_string(), contents.value(i).to_string()));
-                    if matches.len() >= limit {
-                        return Ok(matches);
-                    }
-                }
+    for batch in &results {
+        for hit in hits_from_batch(batch, "_score")? {
+            if !filters.keeps(&hit.content) {
+                continue;
             }
-        }
-    } else {
-        let mut seen_paths = std::collections::HashSet::new();
-
-        for batch in results {
-            let path_array = batch
-                .column_by_name("path")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-            let content_array = batch
-                .column_by_name("content")
-                .and_then(|c| c.as_any().downcast_ref::<StringArray>());
-
-            if let (Some(paths), Some(contents)) = (path_array, content_array) {
-                for i in 0..batch.num_rows() {
-                    let path = paths.value(i).to_string();
-                    if seen_paths.insert(path.clone()) {
-                        matches.push((path, contents.value(i).to_string()));
-                    }
-                    if matches.len() >= limit {
-                        return Ok(matches);
-                    }
-                }
+            if multi_chunk || seen_paths.insert(hit.path.clone()) {
+                matches.push(hit);
+            }
+            if matches.len() >= limit {
+                return Ok(matches);
             }
         }
     }
@@ -247,68 +259,167 @@ pub async fn search_fts(
     Ok(matches)
 }
 
-pub fn hybrid_merge(
-    vector_results: &[(String, String, f32)],
-    fts_results: &[(String, String)],
-    limit: usize,
-) -> Vec<(String, String, f32)> {
-    let k = 60.0_f32;
+/// Scores rescaled to [0, 1] over their list. A list where every score is the same
+/// maps to 1.
+fn min_max(scores: &[f32]) -> Vec<f32> {
+    let min = scores.iter().copied().fold(f32::INFINITY, f32::min);
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    scores
+        .iter()
+        .map(|s| if max - min > f32::EPSILON { (s - min) / (max - min) } else { 1.0 })
+        .collect()
+}
 
-    let mut rrf_scores: HashMap<String, (String, f32)> = HashMap::new();
+/// Container, path, chunk index, and the text of a chunk from before chunks were
+/// numbered: those all share index -1, so only their text tells them apart.
+type ChunkKey = (String, String, i32, String);
 
-    for (rank, (path, snippet, _)) in vector_results.iter().enumerate() {
-        let score = 1.0 / (k + rank as f32 + 1.0);
-        rrf_scores.insert(path.clone(), (snippet.clone(), score));
-    }
+/// Identifies a chunk across result lists and containers.
+fn chunk_key(hit: &SearchHit) -> ChunkKey {
+    let legacy_text = if hit.meta.chunk_index < 0 { hit.content.clone() } else { String::new() };
+    (hit.container.clone(), hit.path.clone(), hit.meta.chunk_index, legacy_text)
+}
 
-    for (rank, (path, snippet)) in fts_results.iter().enumerate() {
-        let score = 1.0 / (k + rank as f32 + 1.0);
-        rrf_scores
-            .entry(path.clone())
-            .and_modify(|(_, s)| *s += score)
-            .or_insert_with(|| (snippet.clone(), score));
-    }
+/// Combines the vector hits (cosine distances) and the BM25 hits into one list scored
+/// higher-is-better, best first. Chunks are fused one by one: two chunks of a file that
+/// each list found stay two hits, each with its own snippet and score.
+pub fn fuse(
+    vector_results: &[SearchHit],
+    fts_results: &[SearchHit],
+    limit: usize,
+    fusion: &FusionConfig,
+) -> Vec<SearchHit> {
+    let mut fused: HashMap<ChunkKey, SearchHit> = HashMap::new();
+    let mut add = |hit: &SearchHit, score: f32| {
+        fused
+            .entry(chunk_key(hit))
+            .and_modify(|merged| merged.score += score)
+            .or_insert_with(|| SearchHit { score, ..hit.clone() });
+    };
 
-    let mut merged: Vec<(String, String, f32)> = rrf_scores
-        .into_iter()
-        .map(|(path, (snippet, score))| (path, snippet, score))
-        .collect();
+    match fusion.strategy {
+        FusionStrategy::Rrf => {
+            let lists = [(vector_results, fusion.vector_weight), (fts_results, fusion.fts_weight)];
+            for (hits, weight) in lists {
+                for (rank, hit) in hits.iter().enumerate() {
+                    add(hit, weight / (fusion.rrf_k + rank as f32 + 1.0));
+                }
+            }
+        }
+        FusionStrategy::Convex => {
+            let total = (fusion.vector_weight + fusion.fts_weight).max(f32::EPSILON);
+            let similarities: Vec<f32> = vector_results.iter().map(|h| 1.0 - h.score).collect();
+            for (hit, score) in vector_results.iter().zip(min_max(&similarities)) {
+                add(hit, score * fusion.vector_weight / total);
+            }
+            let bm25: Vec<f32> = fts_results.iter().map(|h| h.score).collect();
+            for (hit, score) in fts_results.iter().zip(min_max(&bm25)) {
+                add(hit, score * fusion.fts_weight / total);
+            }
+        }
+        FusionStrategy::VectorOnly => {
+            for hit in vector_results {
+                add(hit, 1.0 - hit.score);
+            }
+        }
+        FusionStrategy::FtsOnly => {
+            for hit in fts_results {
+                add(hit, hit.score);
+            }
+        }
+    }
 
-    merged.sort_by(|a, b| b.2.partial_cmp(&a.2).unwrap_or(std::cmp::Ordering::Equal));
+    let mut merged: Vec<SearchHit> = fused.into_values().collect();
+    merged.sort_by(|a, b| b.score.partial_cmp(&a.score).unwrap_or(std::cmp::Ordering::Equal));
     merged.truncate(limit);
     merged
 }
 
-pub async fn search_pipeline(
+/// Keeps the first, i.e. best ranked, hit of each file, in order.
+pub fn best_per_file(hits: Vec<SearchHit>) -> Vec<SearchHit> {
+    let mut seen = std::collections::HashSet::new();
+    hits.into_iter()
+        .filter(|hit| seen.insert((hit.container.clone(), hit.path.clone())))
+        .collect()
+}
+
+/// Every list's first hit, then every list's second, and so on.
+fn interleave(lists: Vec<Vec<SearchHit>>) -> Vec<SearchHit> {
+    let mut iters: Vec<_> = lists.into_iter().map(Vec::into_iter).collect();
+    let mut merged = Vec::new();
+    loop {
+        let before = merged.len();
+        merged.extend(iters.iter_mut().filter_map(Iterator::next));
+        if merged.len() == before {
+            return merged;
+        }
+    }
+}
+
+/// RRF with `k = 60` and equal weights.
+pub fn hybrid_merge(
+    vector_results: &[SearchHit],
+    fts_results: &[SearchHit],
+    limit: usize,
+) -> Vec<SearchHit> {
+    fuse(vector_results, fts_results, limit, &FusionConfig::default())
+}
+
+/// A container to search, with what applies to its table alone.
+#[derive(Debug, Clone)]
+pub struct SearchTarget {
+    pub container: String,
+    pub table: String,
+    /// `path_prefixes` in this container's stored form.
+    pub filters: SearchFilters,
+    pub vectors: VectorConfig,
+}
+
+/// The vector and BM25 lists of one table, chunks tagged with the target's container.
+async fn search_lists(
     db: &Connection,
-    table_name: &str,
-    query: &str,
+    target: &SearchTarget,
+    query_variants: &[String],
     query_vector: &[f32],
-    search_limit: usize,
-    path_prefix: Option<&str>,
-    file_extensions: Option<&[String]>,
-) -> Result<(Vec<(String, String, f32)>, bool)> {
-    let query_variants = super::chunking::expand_query(query);
+    chunk_limit: usize,
+    fusion: &FusionConfig,
+) -> Result<(Vec<SearchHit>, Vec<SearchHit>)> {
+    // Hits select the metadata columns a table indexed by an older build only gets here;
+    // a missing table is reported by the searches themselves
+    if let Ok(table) = db.open_table(&target.table).execute().await {
+        schema::migrate(db, &table, &target.table).await?;
+    }
 
-    let vector_fut = search_files(db, table_name, query_vector, search_limit, path_prefix, file_extensions, false);
+    let vector_fut = async {
+        if fusion.strategy == FusionStrategy::FtsOnly {
+            return Ok(Vec::new());
+        }
+        search_files(
+            db,
+            &target.table,
+            query_vector,
+            chunk_limit,
+            &target.filters,
+            true,
+            &target.vectors,
+        )
+        .await
+    };
 
-    let fts_db = db.clone();
-    let fts_table = table_name.to_string();
-    let fe_clone: Option<Vec<String>> = file_extensions.map(|s| s.to_vec());
-    let pp_clone: Option<String> = path_prefix.map(|s| s.to_string());
-    let fts_fut = async move {
-        let pp_ref = pp_clone.as_deref();
-        let fe_ref = fe_clone.as_deref();
+    let fts_fut = async {
+        if fusion.strategy == FusionStrategy::VectorOnly {
+            return Vec::new();
+        }
         let futs: Vec<_> = query_variants
             .iter()
-            .map(|v| search_fts(&fts_db, &fts_table, v, 30, pp_ref, fe_ref, false))
+            .map(|v| search_fts(db, &target.table, v, FTS_VARIANT_LIMIT, &target.filters, true))
             .collect();
         let results = futures::future::join_all(futs).await;
-        let mut all: Vec<(String, String)> = Vec::new();
+        let mut all: Vec<SearchHit> = Vec::new();
         let mut seen = std::collections::HashSet::new();
         for result in results.into_iter().flatten() {
             for item in result {
-                if seen.insert(item.0.clone()) {
+                if seen.insert(chunk_key(&item)) {
                     all.push(item);
                 }
             }
@@ -316,36 +427,214 @@ pub async fn search_pipeline(
         all
     };
 
-    let (vector_result, fts_results) = tokio::join!(vector_fut, fts_fut);
-    let vector_results = vector_result?;
+    let (vector_result, mut fts_results) = tokio::join!(vector_fut, fts_fut);
+    let mut vector_results = vector_result?;
+    for hit in vector_results.iter_mut().chain(fts_results.iter_mut()) {
+        hit.container = target.container.clone();
+    }
+    Ok((vector_results, fts_results))
+}
 
-    let used_hybrid = !fts_results.is_empty();
-    let merged = if fts_results.is_empty() {
-        vector_results
+/// Vector search and BM25 over the query's variants, run concurrently on every target's
+/// table and fused chunk by chunk as `fusion` says; a single-list strategy skips the
+/// other search. With `per_file`, only the best chunk of each file is kept once fused.
+/// Returns the hits and whether their scores are fused ones, higher is better, rather
+/// than cosine distances.
+pub async fn search_pipeline(
+    db: &Connection,
+    targets: &[SearchTarget],
+    query: &str,
+    query_vector: &[f32],
+    search_limit: usize,
+    fusion: &FusionConfig,
+    per_file: bool,
+) -> Result<(Vec<SearchHit>, bool)> {
+    let query_variants = super::chunking::expand_query(query);
+    // Several chunks of a file may rank high; fetch enough for the collapse to leave
+    // about `search_limit` files
+    let chunk_limit = if per_file { search_limit * CHUNKS_PER_FILE } else { search_limit };
+
+    let searches = targets
+        .iter()
+        .map(|target| search_lists(db, target, &query_variants, query_vector, chunk_limit, fusion));
+    let mut vector_results = Vec::new();
+    let mut fts_lists = Vec::new();
+    for (vector, fts) in futures::future::try_join_all(searches).await? {
+        vector_results.extend(vector);
+        fts_lists.push(fts);
+    }
+    // Distances from the same model compare across tables; BM25 scores don't, each table
+    // has its own term statistics, so those lists are merged by rank
+    vector_results.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap_or(std::cmp::Ordering::Equal));
+    vector_results.truncate(chunk_limit);
+    let fts_results = interleave(fts_lists);
+
+    // Distances as they are: the vector list alone needs no fusing
+    let (mut hits, fused) = if fts_results.is_empty() {
+        (vector_results, false)
     } else {
-        hybrid_merge(&vector_results, &fts_results, search_limit)
+        let all = vector_results.len() + fts_results.len();
+        (fuse(&vector_results, &fts_results, all, fusion), true)
     };
-
-    Ok((merged, used_hybrid))
+    if per_file {
+        hits = best_per_file(hits);
+    }
+    hits.truncate(search_limit);
+    Ok((hits, fused))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn hit(path: &str, content: &str, score: f32) -> SearchHit {
+        SearchHit {
+            path: path.to_string(),
+            content: content.to_string(),
+            score,
+            ..Default::default()
+        }
+    }
+
     #[test]
     fn test_hybrid_merge() {
-        let vector = vec![
-            ("a.txt".to_string(), "hello".to_string(), 0.1),
-            ("b.txt".to_string(), "world".to_string(), 0.2),
-        ];
-        let fts = vec![
-            ("b.txt".to_string(), "world".to_string()),
-            ("c.txt".to_string(), "new".to_string()),
-        ];
+        let vector = vec![hit("a.txt", "hello", 0.1), hit("b.txt", "world", 0.2)];
+        let fts = vec![hit("b.txt", "world", 0.0), hit("c.txt", "new", 0.0)];
         let merged = hybrid_merge(&vector, &fts, 10);
         assert_eq!(merged.len(), 3);
-        assert_eq!(merged[0].0, "b.txt");
+        assert_eq!(merged[0].path, "b.txt");
+    }
+
+    #[test]
+    fn test_hybrid_merge_keeps_chunk_location() {
+        let mut located = hit("a.rs", "fn main", 0.1);
+        located.meta.start_line = 12;
+        located.meta.end_line = 30;
+        let merged = hybrid_merge(&[located], &[hit("a.rs", "fn main", 0.0)], 10);
+        assert_eq!((merged[0].meta.start_line, merged[0].meta.end_line), (12, 30));
+    }
+
+    fn chunk(path: &str, index: i32, content: &str, score: f32) -> SearchHit {
+        let mut hit = hit(path, content, score);
+        hit.meta.chunk_index = index;
+        hit
+    }
+
+    #[test]
+    fn test_fusion_keeps_chunks_apart() {
+        // Vectors and BM25 each found a different chunk of the same file
+        let vector = vec![chunk("a.rs", 0, "fn parse", 0.1), chunk("b.rs", 0, "fn lex", 0.2)];
+        let fts = vec![chunk("b.rs", 0, "fn lex", 5.0), chunk("a.rs", 3, "parse_query", 4.0)];
+        let merged = fuse(&vector, &fts, 10, &FusionConfig::default());
+        assert_eq!(merged.len(), 3);
+        // Only b.rs chunk 0 is in both lists
+        assert_eq!((merged[0].path.as_str(), merged[0].content.as_str()), ("b.rs", "fn lex"));
+        let a_chunks: Vec<&str> = merged
+            .iter()
+            .filter(|h| h.path == "a.rs")
+            .map(|h| h.content.as_str())
+            .collect();
+        assert_eq!(a_chunks, vec!["fn parse", "parse_query"]);
+
+        let files = best_per_file(merged);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1].content, "fn parse");
+    }
+
+    #[test]
+    fn test_unnumbered_chunks_stay_apart() {
+        let vector = vec![chunk("old.md", -1, "intro", 0.1), chunk("old.md", -1, "summary", 0.2)];
+        let fts = vec![chunk("old.md", -1, "summary", 3.0)];
+        let merged = fuse(&vector, &fts, 10, &FusionConfig::default());
+        let contents: Vec<&str> = merged.iter().map(|h| h.content.as_str()).collect();
+        assert_eq!(contents, vec!["summary", "intro"]);
+    }
+
+    #[test]
+    fn test_same_file_in_two_containers_stays_two_hits() {
+        let mut work = hit("@a1/notes.md", "budget", 0.1);
+        work.container = "work".to_string();
+        let mut wiki = hit("@a1/notes.md", "budget", 0.2);
+        wiki.container = "wiki".to_string();
+        let merged = fuse(&[work, wiki.clone()], &[wiki], 10, &FusionConfig::default());
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0].container, "wiki");
+        assert_eq!(best_per_file(merged).len(), 2);
+    }
+
+    #[test]
+    fn test_interleave_merges_by_rank() {
+        let lists = vec![
+            vec![hit("a1", "", 30.0), hit("a2", "", 20.0), hit("a3", "", 10.0)],
+            vec![hit("b1", "", 2.0)],
+        ];
+        let paths: Vec<String> = interleave(lists).into_iter().map(|h| h.path).collect();
+        assert_eq!(paths, vec!["a1", "b1", "a2", "a3"]);
+    }
+
+    #[test]
+    fn test_search_filters_with_dates() {
+        let filters = SearchFilters {
+            extensions: vec!["md".to_string()],
+            modified: Some(TimeWindow { after: Some(100), before: None }),
+            ..Default::default()
+        };
+        assert_eq!(
+            filters.to_filter().map(|f| f.to_sql()),
+            Some("(path LIKE '%.md' ESCAPE '\\') AND mtime >= 100".to_string())
+        );
+    }
+
+    #[test]
+    fn test_search_filters_text_terms() {
+        let filters = SearchFilters {
+            phrases: vec!["Hybrid Merge".to_string()],
+            excluded: vec!["todo".to_string()],
+            ..Default::default()
+        };
+        assert!(filters.keeps("fn hybrid merge() {}"));
+        assert!(!filters.keeps("fn hybrid merge() {} // TODO"));
+        assert!(!filters.keeps("fn merge() {}"));
+        assert!(SearchFilters::default().keeps("anything"));
+    }
+
+    #[test]
+    fn test_weighted_rrf_favors_heavier_list() {
+        let vector = vec![hit("a.md", "", 0.1), hit("b.rs", "", 0.2)];
+        let fts = vec![hit("b.rs", "", 9.0), hit("a.md", "", 3.0)];
+        let tie = fuse(&vector, &fts, 10, &FusionConfig::default());
+        assert_eq!(tie[0].score, tie[1].score);
+
+        let bm25_heavy = FusionConfig { fts_weight: 2.0, ..Default::default() };
+        assert_eq!(fuse(&vector, &fts, 10, &bm25_heavy)[0].path, "b.rs");
+        let vector_heavy = FusionConfig { vector_weight: 2.0, ..Default::default() };
+        assert_eq!(fuse(&vector, &fts, 10, &vector_heavy)[0].path, "a.md");
+    }
+
+    #[test]
+    fn test_convex_normalizes_each_list() {
+        let convex = FusionConfig { strategy: FusionStrategy::Convex, ..Default::default() };
+        let vector = vec![hit("a.md", "", 0.10), hit("b.rs", "", 0.12), hit("c.txt", "", 0.5)];
+        // BM25 scores dwarf similarities; normalized, they weigh the same
+        let fts = vec![hit("b.rs", "", 40.0), hit("c.txt", "", 10.0)];
+        let merged = fuse(&vector, &fts, 10, &convex);
+        assert_eq!(merged[0].path, "b.rs");
+        assert!(merged.iter().all(|h| (0.0..=1.0).contains(&h.score)));
+        assert_eq!(merged.last().unwrap().path, "c.txt");
+    }
+
+    #[test]
+    fn test_single_list_strategies() {
+        let vector = vec![hit("a.md", "", 0.1)];
+        let fts = vec![hit("c.txt", "", 2.0), hit("b.rs", "", 7.0)];
+        let fts_only = FusionConfig { strategy: FusionStrategy::FtsOnly, ..Default::default() };
+        let paths: Vec<String> = fuse(&vector, &fts, 10, &fts_only).into_iter().map(|h| h.path).collect();
+        assert_eq!(paths, vec!["b.rs", "c.txt"]);
+
+        let vector_only = FusionConfig { strategy: FusionStrategy::VectorOnly, ..Default::default() };
+        let merged = fuse(&vector, &fts, 10, &vector_only);
+        assert_eq!(merged.len(), 1);
+        assert!((merged[0].score - 0.9).abs() < 1e-6);
     }
 
     #[test]
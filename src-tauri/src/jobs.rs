@@ -0,0 +1,504 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use tokio::sync::{watch, Mutex};
+
+use crate::config::get_table_name;
+use crate::events::{AppEvent, EventSender};
+
+pub type JobId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JobKind {
+    IndexFolder,
+    ReindexAll,
+    /// Re-embeds a container with a new model into a shadow table, then swaps it in.
+    ReembedModel,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum JobState {
+    Running,
+    Paused,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub kind: JobKind,
+    pub container: String,
+    pub state: JobState,
+}
+
+/// On-disk record of an unfinished job, rewritten at every batch boundary and
+/// removed once the job ends. A file left behind means the app died mid-run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub kind: JobKind,
+    pub container: String,
+    /// Roots not fully walked yet, in processing order.
+    pub roots: Vec<String>,
+    /// Files of the batch being embedded when the checkpoint was written.
+    #[serde(default)]
+    pub pending: Vec<String>,
+    /// Paused when the app quit: resumed paused, not run straight away.
+    #[serde(default)]
+    pub paused: bool,
+}
+
+struct CheckpointFile {
+    path: PathBuf,
+    state: std::sync::Mutex<Checkpoint>,
+}
+
+impl CheckpointFile {
+    fn update(&self, f: impl FnOnce(&mut Checkpoint)) {
+        let mut checkpoint = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        f(&mut checkpoint);
+        if let Err(e) = write_checkpoint(&self.path, &checkpoint) {
+            eprintln!("jobs: checkpoint {:?}: {}", self.path, e);
+        }
+    }
+}
+
+fn write_checkpoint(path: &Path, checkpoint: &Checkpoint) -> Result<()> {
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, serde_json::to_string(checkpoint)?)?;
+    std::fs::rename(&tmp, path)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JobSignal {
+    Run,
+    Pause,
+    Cancel,
+}
+
+/// Returned by [`JobControl::checkpoint`] once the job has been cancelled.
+#[derive(Debug)]
+pub struct JobCancelled;
+
+impl std::fmt::Display for JobCancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Indexing cancelled")
+    }
+}
+
+impl std::error::Error for JobCancelled {}
+
+/// Worker-side handle of a job. Cheap to clone; pass it down to the loops that should obey it.
+#[derive(Clone)]
+pub struct JobControl {
+    signal: watch::Receiver<JobSignal>,
+    checkpoint: Option<Arc<CheckpointFile>>,
+    interrupted: Arc<Vec<String>>,
+}
+
+impl JobControl {
+    /// A control nobody can pause or cancel, for one-off work outside the job manager.
+    pub fn detached() -> Self {
+        let (_tx, signal) = watch::channel(JobSignal::Run);
+        Self {
+            signal,
+            checkpoint: None,
+            interrupted: Arc::new(Vec::new()),
+        }
+    }
+
+    /// Files that were mid-batch when the previous run died. Their stored
+    /// fingerprint can't be trusted, so they are re-extracted unconditionally.
+    pub fn interrupted_files(&self) -> &[String] {
+        &self.interrupted
+    }
+
+    /// Records the batch about to be written, so a crash before commit can replay it.
+    pub fn set_pending(&self, paths: &[String]) {
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint.update(|c| c.pending = paths.to_vec());
+        }
+    }
+
+    /// Marks `root` as fully committed; a resumed run will not walk it again.
+    pub fn root_done(&self, root: &str) {
+        if let Some(checkpoint) = &self.checkpoint {
+            checkpoint.update(|c| {
+                c.roots.retain(|r| r != root);
+                c.pending.clear();
+            });
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.signal.borrow() == JobSignal::Cancel
+    }
+
+    /// Safe point between batches: waits while the job is paused, fails with
+    /// [`JobCancelled`] once it has been cancelled.
+    pub async fn checkpoint(&self) -> Result<()> {
+        let mut signal = self.signal.clone();
+        loop {
+            let current = *signal.borrow_and_update();
+            match current {
+                JobSignal::Run => return Ok(()),
+                JobSignal::Cancel => return Err(JobCancelled.into()),
+                JobSignal::Pause => {}
+            }
+            if signal.changed().await.is_err() {
+                return Ok(());
+            }
+        }
+    }
+}
+
+struct JobEntry {
+    info: JobInfo,
+    signal: watch::Sender<JobSignal>,
+    checkpoint: Arc<CheckpointFile>,
+}
+
+/// Keeps jobs off a container until dropped, see [`JobManager::reserve`].
+pub struct Reservation {
+    reserved: Arc<std::sync::Mutex<HashMap<String, &'static str>>>,
+    container: String,
+}
+
+impl Drop for Reservation {
+    fn drop(&mut self) {
+        self.reserved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.container);
+    }
+}
+
+/// Registry of running indexing jobs, at most one per container since they share its
+/// table and its checkpoint file. Every state change is mirrored as an [`AppEvent`].
+#[derive(Clone)]
+pub struct JobManager {
+    jobs: Arc<Mutex<BTreeMap<JobId, JobEntry>>>,
+    /// Containers changed outside a job, with what is being done to them.
+    reserved: Arc<std::sync::Mutex<HashMap<String, &'static str>>>,
+    next_id: Arc<AtomicU64>,
+    checkpoint_dir: PathBuf,
+    tx: EventSender,
+}
+
+impl JobManager {
+    pub fn new(tx: EventSender, checkpoint_dir: PathBuf) -> Self {
+        std::fs::create_dir_all(&checkpoint_dir).ok();
+        Self {
+            jobs: Arc::new(Mutex::new(BTreeMap::new())),
+            reserved: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            next_id: Arc::new(AtomicU64::new(1)),
+            checkpoint_dir,
+            tx,
+        }
+    }
+
+    /// Refused while another job, even one still cancelling, is registered for `container`,
+    /// or while it is reserved.
+    pub async fn start(
+        &self,
+        kind: JobKind,
+        container: &str,
+        roots: &[String],
+    ) -> Result<(JobId, JobControl), String> {
+        self.launch(Checkpoint {
+            kind,
+            container: container.to_string(),
+            roots: roots.to_vec(),
+            pending: Vec::new(),
+            paused: false,
+        })
+        .await
+    }
+
+    /// Restarts a job from the checkpoint a previous run left behind, paused if it was.
+    pub async fn resume_checkpoint(
+        &self,
+        checkpoint: Checkpoint,
+    ) -> Result<(JobId, JobControl), String> {
+        self.launch(checkpoint).await
+    }
+
+    /// Refuses every job on `container` until the reservation is dropped, for changes that
+    /// a job would undo, like removing a folder whose rows it would write back. `doing`
+    /// tells the refused caller why, e.g. "removing a folder". Refused itself while any
+    /// job, even one still cancelling, is registered for the container.
+    pub async fn reserve(
+        &self,
+        container: &str,
+        doing: &'static str,
+    ) -> Result<Reservation, String> {
+        let jobs = self.jobs.lock().await;
+        if jobs.values().any(|entry| entry.info.container == container) {
+            return Err(format!(
+                "'{}' is being indexed. Cancel the job or wait for it to finish before {}.",
+                container, doing
+            ));
+        }
+        let mut reserved = self.reserved.lock().unwrap_or_else(|e| e.into_inner());
+        if reserved.contains_key(container) {
+            return Err(format!("'{}' is busy, try again in a moment.", container));
+        }
+        reserved.insert(container.to_string(), doing);
+        Ok(Reservation {
+            reserved: self.reserved.clone(),
+            container: container.to_string(),
+        })
+    }
+
+    /// Checkpoints of jobs that never finished, i.e. the app quit or crashed mid-run.
+    pub fn interrupted(&self) -> Vec<Checkpoint> {
+        let Ok(entries) = std::fs::read_dir(&self.checkpoint_dir) else {
+            return Vec::new();
+        };
+        entries
+            .flatten()
+            .filter(|e| e.path().extension().map_or(false, |ext| ext == "json"))
+            .filter_map(|e| std::fs::read_to_string(e.path()).ok())
+            .filter_map(|content| serde_json::from_str(&content).ok())
+            .collect()
+    }
+
+    /// Drops a leftover checkpoint without resuming it.
+    pub fn discard(&self, container: &str) {
+        let _ = std::fs::remove_file(self.checkpoint_path(container));
+    }
+
+    async fn launch(&self, checkpoint: Checkpoint) -> Result<(JobId, JobControl), String> {
+        let mut jobs = self.jobs.lock().await;
+        if jobs.values().any(|entry| entry.info.container == checkpoint.container) {
+            return Err(format!(
+                "'{}' is already being indexed. Wait for that job to finish or cancel it first.",
+                checkpoint.container
+            ));
+        }
+        let doing = self
+            .reserved
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&checkpoint.container)
+            .copied();
+        if let Some(doing) = doing {
+            return Err(format!(
+                "'{}' can't be indexed while {}. Try again once it is done.",
+                checkpoint.container, doing
+            ));
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let (initial, state) = if checkpoint.paused {
+            (JobSignal::Pause, JobState::Paused)
+        } else {
+            (JobSignal::Run, JobState::Running)
+        };
+        let (signal, rx) = watch::channel(initial);
+        let info = JobInfo {
+            id,
+            kind: checkpoint.kind,
+            container: checkpoint.container.clone(),
+            state,
+        };
+        let interrupted = Arc::new(checkpoint.pending.clone());
+        let path = self.checkpoint_path(&checkpoint.container);
+        if let Err(e) = write_checkpoint(&path, &checkpoint) {
+            eprintln!("jobs: checkpoint {:?}: {}", path, e);
+        }
+        let checkpoint = Arc::new(CheckpointFile {
+            path,
+            state: std::sync::Mutex::new(checkpoint),
+        });
+        let control = JobControl {
+            signal: rx,
+            checkpoint: Some(checkpoint.clone()),
+            interrupted,
+        };
+        jobs.insert(
+            id,
+            JobEntry {
+                info: info.clone(),
+                signal,
+                checkpoint,
+            },
+        );
+        drop(jobs);
+        let _ = self.tx.send(AppEvent::JobStarted(info));
+        Ok((id, control))
+    }
+
+    pub async fn list(&self) -> Vec<JobInfo> {
+        self.jobs
+            .lock()
+            .await
+            .values()
+            .map(|entry| entry.info.clone())
+            .collect()
+    }
+
+    pub async fn pause(&self, id: JobId) -> Result<(), String> {
+        self.signal(id, JobSignal::Pause, JobState::Paused).await
+    }
+
+    pub async fn resume(&self, id: JobId) -> Result<(), String> {
+        self.signal(id, JobSignal::Run, JobState::Running).await
+    }
+
+    /// Takes effect at the job's next checkpoint; the batch in flight is still committed.
+    pub async fn cancel(&self, id: JobId) -> Result<(), String> {
+        self.signal(id, JobSignal::Cancel, JobState::Cancelled).await
+    }
+
+    /// Removes the job and reports how it ended, derived from the worker's result.
+    pub async fn finish<T>(&self, id: JobId, result: &Result<T>) {
+        let state = match result {
+            Ok(_) => JobState::Completed,
+            Err(e) if e.downcast_ref::<JobCancelled>().is_some() => JobState::Cancelled,
+            Err(_) => JobState::Failed,
+        };
+        if let Some(entry) = self.jobs.lock().await.remove(&id) {
+            self.discard(&entry.info.container);
+        }
+        let _ = self.tx.send(AppEvent::JobFinished { id, state });
+    }
+
+    fn checkpoint_path(&self, container: &str) -> PathBuf {
+        self.checkpoint_dir
+            .join(format!("{}.json", get_table_name(container)))
+    }
+
+    async fn signal(&self, id: JobId, signal: JobSignal, state: JobState) -> Result<(), String> {
+        let mut jobs = self.jobs.lock().await;
+        let entry = jobs.get_mut(&id).ok_or("Job not found")?;
+        if entry.info.state == JobState::Cancelled {
+            return Err("Job is already cancelling".to_string());
+        }
+        let _ = entry.signal.send(signal);
+        entry.info.state = state;
+        entry.checkpoint.update(|c| c.paused = state == JobState::Paused);
+        let _ = self.tx.send(AppEvent::JobStateChanged { id, state });
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager(name: &str) -> JobManager {
+        let dir = std::env::temp_dir().join(format!("recall_lite_jobs_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        let (tx, _rx) = crate::events::channel();
+        JobManager::new(tx, dir)
+    }
+
+    fn roots() -> Vec<String> {
+        vec!["/code".to_string(), "/docs".to_string()]
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_tracks_progress_until_finish() {
+        let jobs = manager("progress");
+        let (id, job) = jobs.start(JobKind::ReindexAll, "Work", &roots()).await.unwrap();
+        job.set_pending(&["@a1/main.rs".to_string()]);
+
+        let left = jobs.interrupted();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].roots, roots());
+        assert_eq!(left[0].pending, vec!["@a1/main.rs"]);
+
+        job.root_done("/code");
+        let left = jobs.interrupted();
+        assert_eq!(left[0].roots, vec!["/docs"]);
+        assert!(left[0].pending.is_empty());
+
+        jobs.finish(id, &Ok(())).await;
+        assert!(jobs.interrupted().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resume_replays_pending_batch() {
+        let jobs = manager("resume");
+        let (_, job) = jobs.start(JobKind::IndexFolder, "Work", &roots()).await.unwrap();
+        job.set_pending(&["@a1/main.rs".to_string()]);
+
+        // The app died: a new manager finds the checkpoint
+        let (tx, _rx) = crate::events::channel();
+        let restarted = JobManager::new(tx, jobs.checkpoint_dir.clone());
+        let checkpoint = restarted.interrupted().pop().unwrap();
+        let (_, resumed) = restarted.resume_checkpoint(checkpoint).await.unwrap();
+        assert_eq!(resumed.interrupted_files().to_vec(), vec!["@a1/main.rs"]);
+        assert_eq!(restarted.list().await[0].state, JobState::Running);
+    }
+
+    #[tokio::test]
+    async fn test_paused_job_resumes_paused() {
+        let jobs = manager("paused");
+        let (id, _job) = jobs.start(JobKind::ReindexAll, "Work", &roots()).await.unwrap();
+        jobs.pause(id).await.unwrap();
+        let checkpoint = jobs.interrupted().pop().unwrap();
+        assert!(checkpoint.paused);
+
+        let (tx, _rx) = crate::events::channel();
+        let restarted = JobManager::new(tx, jobs.checkpoint_dir.clone());
+        let (_, resumed) = restarted.resume_checkpoint(checkpoint).await.unwrap();
+        assert_eq!(restarted.list().await[0].state, JobState::Paused);
+        assert_eq!(*resumed.signal.borrow(), JobSignal::Pause);
+    }
+
+    #[tokio::test]
+    async fn test_one_job_per_container() {
+        let jobs = manager("busy");
+        let (id, _job) = jobs.start(JobKind::ReindexAll, "Work", &roots()).await.unwrap();
+        assert!(jobs.start(JobKind::IndexFolder, "Work", &roots()).await.is_err());
+        assert!(jobs.start(JobKind::IndexFolder, "Wiki", &roots()).await.is_ok());
+
+        // Still committing its last batch
+        jobs.cancel(id).await.unwrap();
+        assert!(jobs.start(JobKind::IndexFolder, "Work", &roots()).await.is_err());
+        // The refused start left the running job's checkpoint alone
+        let work: Vec<Checkpoint> = jobs
+            .interrupted()
+            .into_iter()
+            .filter(|c| c.container == "Work")
+            .collect();
+        assert_eq!(work[0].kind, JobKind::ReindexAll);
+
+        jobs.finish(id, &Err::<(), _>(JobCancelled.into())).await;
+        assert!(jobs.start(JobKind::IndexFolder, "Work", &roots()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_reservation_excludes_jobs() {
+        let jobs = manager("reserve");
+        let (id, _job) = jobs.start(JobKind::ReindexAll, "Work", &roots()).await.unwrap();
+        jobs.cancel(id).await.unwrap();
+        // A cancelled job still commits its last batch
+        assert!(jobs.reserve("Work", "removing a folder").await.is_err());
+        jobs.finish(id, &Err::<(), _>(JobCancelled.into())).await;
+
+        let reservation = jobs.reserve("Work", "removing a folder").await.unwrap();
+        assert!(jobs.reserve("Work", "removing a folder").await.is_err());
+        assert!(jobs.start(JobKind::IndexFolder, "Work", &roots()).await.is_err());
+        assert!(jobs.start(JobKind::IndexFolder, "Wiki", &roots()).await.is_ok());
+
+        drop(reservation);
+        assert!(jobs.start(JobKind::IndexFolder, "Work", &roots()).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_discard_removes_leftover() {
+        let jobs = manager("discard");
+        let _ = jobs.start(JobKind::IndexFolder, "Work", &roots()).await.unwrap();
+        jobs.discard("Work");
+        assert!(jobs.interrupted().is_empty());
+    }
+}
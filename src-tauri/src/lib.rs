@@ -3,6 +3,7 @@ pub mod config;
 pub mod events;
 pub mod i18n;
 pub mod indexer;
+pub mod jobs;
 pub mod state;
 pub mod ui;
 pub mod watcher;
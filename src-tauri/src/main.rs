@@ -5,10 +5,12 @@ use std::sync::Arc;
 use mimalloc::MiMalloc;
 use tokio::sync::Mutex;
 
+use recall_lite_lib::commands;
 use recall_lite_lib::config::{self, get_embedding_model, ConfigState};
 use recall_lite_lib::events::{self, AppEvent};
 use recall_lite_lib::i18n::{self, Language};
 use recall_lite_lib::indexer;
+use recall_lite_lib::jobs::JobManager;
 use recall_lite_lib::state::{DbState, ModelState, RerankerState};
 use recall_lite_lib::ui::RecallApp;
 use recall_lite_lib::watcher;
@@ -37,6 +39,7 @@ fn main() {
     let launch_at_startup = config.launch_at_startup;
     let hotkey_str = config.hotkey.clone();
     let model_enum = get_embedding_model(&config.embedding_model);
+    let model_identity = indexer::embedding::ModelIdentity::new(&config.embedding_model);
 
     // Determine locale
     let locale = if config.locale == "auto" {
@@ -86,6 +89,9 @@ fn main() {
         model: None,
         init_error: None,
         cached_dim: None,
+        identity: model_identity,
+        legacy: None,
+        cache_dir: app_data.join("models"),
     }));
     let reranker_state = Arc::new(Mutex::new(RerankerState {
         reranker: None,
@@ -99,10 +105,11 @@ fn main() {
 
     // ── Event channel ──
     let (event_tx, event_rx) = events::channel();
+    let job_manager = JobManager::new(event_tx.clone(), config_dir.join("checkpoints"));
 
     // ── Initial container list ──
     let (initial_containers, initial_active) = runtime.block_on(async {
-        match recall_lite_lib::commands::get_containers(&config_state).await {
+        match commands::get_containers(&config_state).await {
             Ok((list, active)) => (list, active),
             Err(_) => (vec![], "Default".to_string()),
         }
@@ -144,6 +151,8 @@ fn main() {
     };
     let watcher_db = db.clone();
     let watcher_model = model_state.clone();
+    let resume_db = db_state.clone();
+    let resume_jobs = job_manager.clone();
     let models_path = app_data.join("models");
     std::fs::create_dir_all(&models_path).ok();
 
@@ -177,6 +186,31 @@ fn main() {
                     )
                     .await;
 
+                    drop(state);
+                    // Tables from before root ids still store absolute paths
+                    let _ = commands::rebase_paths(&resume_db, &config_state_for_watcher).await;
+
+                    // Containers still on a previous model are re-embedded first,
+                    // their catch-up pass covers any job they had left unfinished
+                    let _ = commands::migrate_models(
+                        &resume_db,
+                        &model_state_clone,
+                        &config_state_for_watcher,
+                        &resume_jobs,
+                        event_tx_clone.clone(),
+                    )
+                    .await;
+
+                    // Finish whatever indexing the previous session left behind
+                    let _ = commands::resume_interrupted_jobs(
+                        &resume_db,
+                        &model_state_clone,
+                        &config_state_for_watcher,
+                        &resume_jobs,
+                        event_tx_clone.clone(),
+                    )
+                    .await;
+
                     break;
                 }
                 Err(e) => {
@@ -211,6 +245,16 @@ fn main() {
         }
     });
 
+    // Compaction and index refresh in the background
+    runtime.spawn(indexer::maintenance::run_scheduler(
+        db_state.clone(),
+        ConfigState {
+            config: config_state.config.clone(),
+            path: config_state.path.clone(),
+        },
+        job_manager.clone(),
+    ));
+
     // Clean up legacy cache
     if let Ok(home) = std::env::var("USERPROFILE").or_else(|_| std::env::var("HOME")) {
         let legacy_cache = std::path::PathBuf::from(home).join(".fastembed_cache");
@@ -253,6 +297,7 @@ fn main() {
                 reranker_state,
                 config_state,
                 watcher_state,
+                job_manager,
                 event_tx,
                 event_rx,
                 rt_handle,
@@ -9,6 +9,11 @@ pub struct ModelState {
     pub model: Option<fastembed::TextEmbedding>,
     pub init_error: Option<String>,
     pub cached_dim: Option<usize>,
+    /// Model the config asks for, checked against what each table was built with.
+    pub identity: crate::indexer::embedding::ModelIdentity,
+    /// Previous model of a container being re-embedded, so its live table stays searchable.
+    pub legacy: Option<crate::indexer::embedding::LegacyModel>,
+    pub cache_dir: std::path::PathBuf,
 }
 
 pub struct RerankerState {
@@ -18,9 +23,11 @@ pub struct RerankerState {
 
 #[derive(Clone)]
 pub struct SearchResult {
+    pub container: String,
     pub path: String,
     pub snippet: String,
     pub score: f32,
+    pub meta: crate::indexer::db::ChunkMeta,
 }
 
 #[derive(Clone)]
@@ -35,4 +42,6 @@ pub struct ContainerListItem {
     pub name: String,
     pub description: String,
     pub indexed_paths: Vec<String>,
+    /// Searched along with the active container.
+    pub searched: bool,
 }
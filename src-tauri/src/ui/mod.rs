@@ -18,6 +18,10 @@ use crate::commands;
 use crate::config::ConfigState;
 use crate::events::{AppEvent, EventReceiver, EventSender};
 use crate::i18n::{self, Language};
+use crate::indexer::archive::ARCHIVE_EXTENSION;
+use crate::indexer::doctor::Issue;
+use crate::indexer::report::{reports_dir, IndexReport};
+use crate::jobs::{JobInfo, JobKind, JobManager};
 use crate::state::{
     ContainerListItem, DbState, IndexingProgress, ModelState, RerankerState, SearchResult,
 };
@@ -25,17 +29,23 @@ use crate::watcher;
 
 use self::modal::ModalState;
 use self::settings_panel::SettingsAction;
+use self::status_bar::StatusAction;
 
 /// Types de reponses asynchrones envoyees depuis les tasks vers l'UI
 enum AsyncResponse {
     SearchResults {
         generation: u64,
-        results: Result<Vec<SearchResult>, String>,
+        results: Result<(Vec<SearchResult>, Vec<String>), String>,
     },
     IndexResult(Result<String, String>),
     ClearResult(Result<(), String>),
     ContainerList(Result<(Vec<ContainerListItem>, String), String>),
     ContainerAction(Result<(), String>),
+    JobAction(Result<(), String>),
+    FolderRemoved(Result<String, String>),
+    Imported(Result<String, String>),
+    IndexChecked(Result<Vec<Issue>, String>),
+    SearchScopeChanged(Result<(), String>),
 }
 
 pub struct RecallApp {
@@ -47,6 +57,9 @@ pub struct RecallApp {
     status_clear_at: Option<Instant>,
     is_indexing: bool,
     index_progress: Option<IndexingProgress>,
+    jobs: Vec<JobInfo>,
+    // Dernier rapport d'indexation du conteneur actif
+    last_report: Option<IndexReport>,
 
     // Conteneurs
     containers: Vec<ContainerListItem>,
@@ -67,6 +80,7 @@ pub struct RecallApp {
     reranker_state: Arc<Mutex<RerankerState>>,
     config_state: ConfigState,
     watcher_state: watcher::WatcherState,
+    job_manager: JobManager,
 
     // Canaux d'evenements
     event_tx: EventSender,
@@ -104,6 +118,7 @@ impl RecallApp {
         reranker_state: Arc<Mutex<RerankerState>>,
         config_state: ConfigState,
         watcher_state: watcher::WatcherState,
+        job_manager: JobManager,
         event_tx: EventSender,
         event_rx: EventReceiver,
         runtime: tokio::runtime::Handle,
@@ -112,6 +127,7 @@ impl RecallApp {
         initial_active: String,
     ) -> Self {
         let (async_tx, async_rx) = std::sync::mpsc::channel();
+        let last_report = IndexReport::load(&reports_dir(&config_state.path), &initial_active);
 
         Self {
             query: String::new(),
@@ -121,6 +137,8 @@ impl RecallApp {
             status_clear_at: None,
             is_indexing: false,
             index_progress: None,
+            jobs: Vec::new(),
+            last_report,
 
             containers: initial_containers,
             active_container: initial_active,
@@ -135,6 +153,7 @@ impl RecallApp {
             reranker_state,
             config_state,
             watcher_state,
+            job_manager,
 
             event_tx,
             event_rx,
@@ -214,6 +233,50 @@ impl RecallApp {
                     self.index_progress = None;
                 }
                 AppEvent::RerankerLoaded | AppEvent::RerankerLoadError(_) => {}
+                AppEvent::JobStarted(info) => {
+                    self.jobs.push(info);
+                }
+                AppEvent::JobStateChanged { id, state } => {
+                    if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+                        job.state = state;
+                    }
+                }
+                AppEvent::JobFinished { id, .. } => {
+                    self.jobs.retain(|j| j.id != id);
+                }
+                AppEvent::IndexingReport { container, report } => {
+                    if container == self.active_container {
+                        self.last_report = Some(report);
+                    }
+                }
+                AppEvent::ReembedProgress { container, current, total } => {
+                    self.is_indexing = true;
+                    self.index_progress = Some(IndexingProgress {
+                        current,
+                        total,
+                        path: container.clone(),
+                    });
+                    let (current, total) = (current.to_string(), total.to_string());
+                    self.status = i18n::t(
+                        self.locale,
+                        "status_reembedding",
+                        &[("name", &container), ("current", &current), ("total", &total)],
+                    );
+                }
+                AppEvent::RebuildRequired { container, reason } => {
+                    // Le re-embedding en cours reglera deja le probleme
+                    let reembedding = self
+                        .jobs
+                        .iter()
+                        .any(|j| j.kind == JobKind::ReembedModel && j.container == container);
+                    // Une seule demande a la fois, les suivantes reviendront au prochain essai
+                    if !reembedding && matches!(self.modal, ModalState::None) {
+                        self.modal = ModalState::ConfirmSchemaRebuild {
+                            container_name: container,
+                            reason,
+                        };
+                    }
+                }
             }
             ctx.request_repaint();
         }
@@ -224,9 +287,17 @@ impl RecallApp {
                 AsyncResponse::SearchResults { generation, results } => {
                     if generation == self.search_generation {
                         match results {
-                            Ok(res) => {
+                            Ok((res, skipped)) => {
                                 self.results = res;
                                 self.selected_index = 0;
+                                // Conteneurs sur un autre modele que le conteneur actif
+                                if !skipped.is_empty() {
+                                    self.status = i18n::t(
+                                        self.locale,
+                                        "status_search_skipped",
+                                        &[("containers", &skipped.join(", "))],
+                                    );
+                                }
                             }
                             Err(msg) => {
                                 if msg.contains("rebuild") || msg.contains("Model changed") {
@@ -272,6 +343,50 @@ impl RecallApp {
                     }
                     self.refresh_containers(ctx);
                 }
+                AsyncResponse::FolderRemoved(result) => {
+                    self.status = match result {
+                        Ok(msg) => msg,
+                        Err(msg) => msg,
+                    };
+                    self.status_clear_at = Some(Instant::now() + Duration::from_secs(4));
+                    self.refresh_containers(ctx);
+                }
+                AsyncResponse::Imported(result) => {
+                    self.status = match result {
+                        Ok(msg) => msg,
+                        Err(msg) => msg,
+                    };
+                    self.is_indexing = false;
+                    self.refresh_containers(ctx);
+                }
+                AsyncResponse::SearchScopeChanged(result) => {
+                    if let Err(msg) = result {
+                        self.status = msg;
+                    }
+                    // Relance la recherche courante sur les nouveaux conteneurs
+                    self.last_searched_query.clear();
+                    self.refresh_containers(ctx);
+                }
+                AsyncResponse::JobAction(result) => {
+                    if let Err(msg) = result {
+                        self.status = msg;
+                    }
+                }
+                AsyncResponse::IndexChecked(result) => {
+                    self.is_indexing = false;
+                    match result {
+                        Ok(issues) if issues.is_empty() => {
+                            self.status = i18n::ts(self.locale, "status_check_ok");
+                            self.status_clear_at =
+                                Some(Instant::now() + Duration::from_secs(4));
+                        }
+                        Ok(issues) => {
+                            self.status.clear();
+                            self.modal = ModalState::ConfirmRepair { issues };
+                        }
+                        Err(msg) => self.status = msg,
+                    }
+                }
             }
             ctx.request_repaint();
         }
@@ -459,19 +574,85 @@ impl RecallApp {
                 path: self.config_state.path.clone(),
             };
             let ws = self.watcher_state.clone();
+            let jobs = self.job_manager.clone();
             let event_tx = self.event_tx.clone();
             let async_tx = self.async_tx.clone();
             let repaint = ctx.clone();
 
             self.runtime.spawn(async move {
                 let result =
-                    commands::index_folder(dir, &db, &model, &config, &ws, event_tx).await;
+                    commands::index_folder(dir, &db, &model, &config, &ws, &jobs, event_tx)
+                        .await;
                 let _ = async_tx.send(AsyncResponse::IndexResult(result));
                 repaint.request_repaint();
             });
         }
     }
 
+    fn export_container(&mut self, ctx: &egui::Context) {
+        let title = i18n::t(
+            self.locale,
+            "export_title",
+            &[("container", &self.active_container)],
+        );
+        let selected = rfd::FileDialog::new()
+            .set_title(&title)
+            .set_file_name(format!("{}.{}", self.active_container, ARCHIVE_EXTENSION))
+            .add_filter("Recall-Lite", &[ARCHIVE_EXTENSION])
+            .save_file();
+        self.suppress_hide_until =
+            Some(Instant::now() + Duration::from_millis(500));
+
+        if let Some(dest) = selected {
+            self.status = i18n::ts(self.locale, "status_exporting");
+            self.is_indexing = true;
+
+            let container = self.active_container.clone();
+            let db = self.db_state.clone();
+            let model = self.model_state.clone();
+            let config = ConfigState {
+                config: self.config_state.config.clone(),
+                path: self.config_state.path.clone(),
+            };
+            let tx = self.async_tx.clone();
+            let repaint = ctx.clone();
+            self.runtime.spawn(async move {
+                let result =
+                    commands::export_container(container, dest, &db, &model, &config).await;
+                let _ = tx.send(AsyncResponse::IndexResult(result));
+                repaint.request_repaint();
+            });
+        }
+    }
+
+    fn import_container(&mut self, ctx: &egui::Context) {
+        let selected = rfd::FileDialog::new()
+            .set_title(i18n::ts(self.locale, "import_title"))
+            .add_filter("Recall-Lite", &[ARCHIVE_EXTENSION])
+            .pick_file();
+        self.suppress_hide_until =
+            Some(Instant::now() + Duration::from_millis(500));
+
+        if let Some(src) = selected {
+            self.status = i18n::ts(self.locale, "status_importing");
+            self.is_indexing = true;
+
+            let db = self.db_state.clone();
+            let model = self.model_state.clone();
+            let config = ConfigState {
+                config: self.config_state.config.clone(),
+                path: self.config_state.path.clone(),
+            };
+            let tx = self.async_tx.clone();
+            let repaint = ctx.clone();
+            self.runtime.spawn(async move {
+                let result = commands::import_container(src, &db, &model, &config).await;
+                let _ = tx.send(AsyncResponse::Imported(result));
+                repaint.request_repaint();
+            });
+        }
+    }
+
     fn refresh_containers(&self, ctx: &egui::Context) {
         let config = ConfigState {
             config: self.config_state.config.clone(),
@@ -491,6 +672,7 @@ impl RecallApp {
             return;
         }
         self.active_container = name.clone();
+        self.last_report = IndexReport::load(&reports_dir(&self.config_state.path), &name);
         self.results.clear();
         self.query.clear();
         self.status =
@@ -515,6 +697,26 @@ impl RecallApp {
         });
     }
 
+    fn toggle_searched(&mut self, name: String, ctx: &egui::Context) {
+        let Some(item) = self.containers.iter_mut().find(|c| c.name == name) else {
+            return;
+        };
+        item.searched = !item.searched;
+        let searched = item.searched;
+
+        let config = ConfigState {
+            config: self.config_state.config.clone(),
+            path: self.config_state.path.clone(),
+        };
+        let tx = self.async_tx.clone();
+        let repaint = ctx.clone();
+        self.runtime.spawn(async move {
+            let result = commands::set_container_searched(name, searched, &config).await;
+            let _ = tx.send(AsyncResponse::SearchScopeChanged(result));
+            repaint.request_repaint();
+        });
+    }
+
     fn create_container(&mut self, name: String, description: String, ctx: &egui::Context) {
         let config = ConfigState {
             config: self.config_state.config.clone(),
@@ -569,6 +771,87 @@ impl RecallApp {
         });
     }
 
+    fn remove_folder(&mut self, dir: String, ctx: &egui::Context) {
+        self.results.clear();
+
+        let db = self.db_state.clone();
+        let model = self.model_state.clone();
+        let config = ConfigState {
+            config: self.config_state.config.clone(),
+            path: self.config_state.path.clone(),
+        };
+        let ws = self.watcher_state.clone();
+        let jobs = self.job_manager.clone();
+        let event_tx = self.event_tx.clone();
+        let tx = self.async_tx.clone();
+        let repaint = ctx.clone();
+        self.runtime.spawn(async move {
+            let result =
+                commands::remove_folder(dir, &db, &model, &config, &ws, &jobs, event_tx).await;
+            let _ = tx.send(AsyncResponse::FolderRemoved(result));
+            repaint.request_repaint();
+        });
+    }
+
+    fn prune_index(&mut self, ctx: &egui::Context) {
+        self.status = i18n::ts(self.locale, "status_pruning");
+        self.is_indexing = true;
+
+        let db = self.db_state.clone();
+        let config = ConfigState {
+            config: self.config_state.config.clone(),
+            path: self.config_state.path.clone(),
+        };
+        let tx = self.async_tx.clone();
+        let repaint = ctx.clone();
+        self.runtime.spawn(async move {
+            let result = commands::prune_index(&db, &config).await;
+            let _ = tx.send(AsyncResponse::IndexResult(result));
+            repaint.request_repaint();
+        });
+    }
+
+    fn check_index(&mut self, ctx: &egui::Context) {
+        self.status = i18n::ts(self.locale, "status_checking");
+        self.is_indexing = true;
+
+        let db = self.db_state.clone();
+        let model = self.model_state.clone();
+        let config = ConfigState {
+            config: self.config_state.config.clone(),
+            path: self.config_state.path.clone(),
+        };
+        let jobs = self.job_manager.clone();
+        let tx = self.async_tx.clone();
+        let repaint = ctx.clone();
+        self.runtime.spawn(async move {
+            let result = commands::check_index(&db, &model, &config, &jobs).await;
+            let _ = tx.send(AsyncResponse::IndexChecked(result));
+            repaint.request_repaint();
+        });
+    }
+
+    // Repare uniquement les problemes confirmes dans la modale
+    fn repair_index(&mut self, issues: Vec<Issue>, ctx: &egui::Context) {
+        self.status = i18n::ts(self.locale, "status_repairing");
+        self.is_indexing = true;
+
+        let db = self.db_state.clone();
+        let config = ConfigState {
+            config: self.config_state.config.clone(),
+            path: self.config_state.path.clone(),
+        };
+        let jobs = self.job_manager.clone();
+        let event_tx = self.event_tx.clone();
+        let tx = self.async_tx.clone();
+        let repaint = ctx.clone();
+        self.runtime.spawn(async move {
+            let result = commands::repair_index(issues, &db, &config, &jobs, event_tx).await;
+            let _ = tx.send(AsyncResponse::IndexResult(result));
+            repaint.request_repaint();
+        });
+    }
+
     fn reindex_all(&mut self, ctx: &egui::Context) {
         self.status = i18n::ts(self.locale, "status_rebuilding");
         self.is_indexing = true;
@@ -580,15 +863,57 @@ impl RecallApp {
             config: self.config_state.config.clone(),
             path: self.config_state.path.clone(),
         };
+        let jobs = self.job_manager.clone();
         let event_tx = self.event_tx.clone();
         let tx = self.async_tx.clone();
         let repaint = ctx.clone();
         self.runtime.spawn(async move {
-            let result = commands::reindex_all(&db, &model, &config, event_tx).await;
+            let result = commands::reindex_all(&db, &model, &config, &jobs, event_tx).await;
+            let _ = tx.send(AsyncResponse::IndexResult(result));
+            repaint.request_repaint();
+        });
+    }
+
+    fn rebuild_container(&mut self, container: String, ctx: &egui::Context) {
+        self.status = i18n::ts(self.locale, "status_rebuilding");
+        self.is_indexing = true;
+        if container == self.active_container {
+            self.results.clear();
+        }
+
+        let db = self.db_state.clone();
+        let model = self.model_state.clone();
+        let config = ConfigState {
+            config: self.config_state.config.clone(),
+            path: self.config_state.path.clone(),
+        };
+        let jobs = self.job_manager.clone();
+        let event_tx = self.event_tx.clone();
+        let tx = self.async_tx.clone();
+        let repaint = ctx.clone();
+        self.runtime.spawn(async move {
+            let result =
+                commands::rebuild_container(container, &db, &model, &config, &jobs, event_tx).await;
             let _ = tx.send(AsyncResponse::IndexResult(result));
             repaint.request_repaint();
         });
     }
+
+    fn control_job(&mut self, action: StatusAction, ctx: &egui::Context) {
+        let jobs = self.job_manager.clone();
+        let tx = self.async_tx.clone();
+        let repaint = ctx.clone();
+        self.runtime.spawn(async move {
+            let result = match action {
+                StatusAction::None => return,
+                StatusAction::PauseJob(id) => commands::pause_job(id, &jobs).await,
+                StatusAction::ResumeJob(id) => commands::resume_job(id, &jobs).await,
+                StatusAction::CancelJob(id) => commands::cancel_job(id, &jobs).await,
+            };
+            let _ = tx.send(AsyncResponse::JobAction(result));
+            repaint.request_repaint();
+        });
+    }
 }
 
 impl eframe::App for RecallApp {
@@ -624,10 +949,18 @@ impl eframe::App for RecallApp {
 
         egui::CentralPanel::default().frame(frame).show(ctx, |ui| {
             // 1. Barre de recherche
+            // Conteneur actif, puis ceux cherches avec lui
+            let mut scope = vec![self.active_container.as_str()];
+            scope.extend(
+                self.containers
+                    .iter()
+                    .filter(|c| c.searched && c.name != self.active_container)
+                    .map(|c| c.name.as_str()),
+            );
             let placeholder = i18n::t(
                 self.locale,
                 "search_placeholder",
-                &[("container", &self.active_container)],
+                &[("container", &scope.join(", "))],
             );
             let old_query = self.query.clone();
             let mut open_settings = false;
@@ -666,16 +999,21 @@ impl eframe::App for RecallApp {
                 .iter()
                 .find(|c| c.name == self.active_container);
             let folder_count = active_info.map(|i| i.indexed_paths.len()).unwrap_or(0);
-            status_bar::show(
+            let status_action = status_bar::show(
                 ui,
                 &self.status,
                 self.is_indexing,
                 self.index_progress.as_ref(),
+                &self.jobs,
+                self.last_report.as_ref(),
                 &self.active_container,
                 folder_count,
                 self.results.len(),
                 self.locale,
             );
+            if !matches!(status_action, StatusAction::None) {
+                self.control_job(status_action, ctx);
+            }
         });
 
         // ── Panneau de reglages (overlay, par-dessus le panel central) ──
@@ -698,6 +1036,9 @@ impl eframe::App for RecallApp {
                     self.switch_container(name, ctx);
                     self.focus_pending = true;
                 }
+                SettingsAction::ToggleSearched(name) => {
+                    self.toggle_searched(name, ctx);
+                }
                 SettingsAction::CreateContainer => {
                     self.settings_open = false;
                     self.modal = ModalState::CreateContainer {
@@ -730,6 +1071,29 @@ impl eframe::App for RecallApp {
                         folder_count,
                     };
                 }
+                SettingsAction::PruneIndex => {
+                    self.settings_open = false;
+                    self.prune_index(ctx);
+                }
+                SettingsAction::CheckIndex => {
+                    self.settings_open = false;
+                    self.check_index(ctx);
+                }
+                SettingsAction::RemoveFolder(path) => {
+                    self.settings_open = false;
+                    self.modal = ModalState::ConfirmRemoveFolder {
+                        container_name: self.active_container.clone(),
+                        path,
+                    };
+                }
+                SettingsAction::ExportContainer => {
+                    self.settings_open = false;
+                    self.export_container(ctx);
+                }
+                SettingsAction::ImportContainer => {
+                    self.settings_open = false;
+                    self.import_container(ctx);
+                }
                 SettingsAction::AddFolder => {
                     self.settings_open = false;
                     self.pick_folder(ctx);
@@ -774,6 +1138,15 @@ impl eframe::App for RecallApp {
             modal::ModalResult::ConfirmReindex => {
                 self.reindex_all(ctx);
             }
+            modal::ModalResult::ConfirmRemoveFolder { path } => {
+                self.remove_folder(path, ctx);
+            }
+            modal::ModalResult::ConfirmSchemaRebuild { container } => {
+                self.rebuild_container(container, ctx);
+            }
+            modal::ModalResult::ConfirmRepair { issues } => {
+                self.repair_index(issues, ctx);
+            }
         }
 
         self.maybe_search(ctx);
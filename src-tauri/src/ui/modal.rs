@@ -1,6 +1,7 @@
 use eframe::egui;
 
 use crate::i18n::{self, Language};
+use crate::indexer::doctor::Issue;
 
 use super::style;
 
@@ -20,6 +21,17 @@ pub enum ModalState {
         container_name: String,
         folder_count: usize,
     },
+    ConfirmRemoveFolder {
+        container_name: String,
+        path: String,
+    },
+    ConfirmSchemaRebuild {
+        container_name: String,
+        reason: String,
+    },
+    ConfirmRepair {
+        issues: Vec<Issue>,
+    },
 }
 
 
@@ -29,6 +41,9 @@ pub enum ModalResult {
     ConfirmDelete,
     ConfirmClear,
     ConfirmReindex,
+    ConfirmRemoveFolder { path: String },
+    ConfirmSchemaRebuild { container: String },
+    ConfirmRepair { issues: Vec<Issue> },
 }
 
 pub fn show(ctx: &egui::Context, modal: &mut ModalState, locale: Language) -> ModalResult {
@@ -195,6 +210,73 @@ pub fn show(ctx: &egui::Context, modal: &mut ModalState, locale: Language) -> Mo
                 ModalResult::ConfirmReindex,
             );
         }
+
+        ModalState::ConfirmRemoveFolder { container_name, path } => {
+            let msg = i18n::t(
+                locale,
+                "dialog_remove_folder_message",
+                &[("name", container_name), ("path", path)],
+            );
+            let on_confirm = ModalResult::ConfirmRemoveFolder { path: path.clone() };
+            show_confirm_modal(
+                ctx,
+                locale,
+                "dialog_remove_folder_title",
+                &msg,
+                "dialog_remove_folder_confirm",
+                true,
+                &mut result,
+                &mut close,
+                on_confirm,
+            );
+        }
+
+        ModalState::ConfirmSchemaRebuild { container_name, reason } => {
+            let msg = i18n::t(
+                locale,
+                "dialog_schema_rebuild_message",
+                &[("name", container_name), ("reason", reason)],
+            );
+            let on_confirm = ModalResult::ConfirmSchemaRebuild {
+                container: container_name.clone(),
+            };
+            show_confirm_modal(
+                ctx,
+                locale,
+                "dialog_schema_rebuild_title",
+                &msg,
+                "dialog_schema_rebuild_confirm",
+                true,
+                &mut result,
+                &mut close,
+                on_confirm,
+            );
+        }
+
+        ModalState::ConfirmRepair { issues } => {
+            let count = issues.len().to_string();
+            let list = issues
+                .iter()
+                .map(|i| format!("\u{2022} {}", i.describe()))
+                .collect::<Vec<_>>()
+                .join("\n");
+            let msg = i18n::t(
+                locale,
+                "dialog_repair_message",
+                &[("count", &count), ("issues", &list)],
+            );
+            show_confirm_modal(
+                ctx,
+                locale,
+                "dialog_repair_title",
+                &msg,
+                "dialog_repair_confirm",
+                false,
+                &mut result,
+                &mut close,
+                ModalResult::ConfirmRepair { issues: issues.clone() },
+            );
+        }
     }
 
     if close {
@@ -292,6 +374,17 @@ fn show_confirm_modal(
                                 ModalResult::ConfirmDelete => ModalResult::ConfirmDelete,
                                 ModalResult::ConfirmClear => ModalResult::ConfirmClear,
                                 ModalResult::ConfirmReindex => ModalResult::ConfirmReindex,
+                                ModalResult::ConfirmRemoveFolder { path } => {
+                                    ModalResult::ConfirmRemoveFolder { path: path.clone() }
+                                }
+                                ModalResult::ConfirmSchemaRebuild { container } => {
+                                    ModalResult::ConfirmSchemaRebuild {
+                                        container: container.clone(),
+                                    }
+                                }
+                                ModalResult::ConfirmRepair { issues } => {
+                                    ModalResult::ConfirmRepair { issues: issues.clone() }
+                                }
                                 _ => ModalResult::None,
                             };
                             *close = true;
@@ -317,6 +410,15 @@ fn show_confirm_modal(
             ModalResult::ConfirmDelete => ModalResult::ConfirmDelete,
             ModalResult::ConfirmClear => ModalResult::ConfirmClear,
             ModalResult::ConfirmReindex => ModalResult::ConfirmReindex,
+            ModalResult::ConfirmRemoveFolder { path } => {
+                ModalResult::ConfirmRemoveFolder { path: path.clone() }
+            }
+            ModalResult::ConfirmSchemaRebuild { container } => ModalResult::ConfirmSchemaRebuild {
+                container: container.clone(),
+            },
+            ModalResult::ConfirmRepair { issues } => ModalResult::ConfirmRepair {
+                issues: issues.clone(),
+            },
             _ => ModalResult::None,
         };
         *close = true;
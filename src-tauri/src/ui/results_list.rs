@@ -1,5 +1,6 @@
 use eframe::egui;
 
+use crate::indexer::ocr::is_image_extension;
 use crate::state::SearchResult;
 
 use super::style;
@@ -28,6 +29,19 @@ fn get_filename(path: &str) -> &str {
     path.rsplit(['/', '\\']).next().unwrap_or(path)
 }
 
+/// "L12-40" pour les fichiers texte ; rien pour PDF / images ou les lignes ne parlent pas.
+fn line_range(result: &SearchResult) -> Option<String> {
+    let meta = &result.meta;
+    if meta.start_line <= 0 || meta.extension == "pdf" || is_image_extension(&meta.extension) {
+        return None;
+    }
+    if meta.end_line > meta.start_line {
+        Some(format!("L{}-{}", meta.start_line, meta.end_line))
+    } else {
+        Some(format!("L{}", meta.start_line))
+    }
+}
+
 pub fn show(
     ui: &mut egui::Ui,
     results: &[SearchResult],
@@ -39,6 +53,9 @@ pub fn show(
         return action;
     }
 
+    // Le conteneur n'est affiche que si les resultats viennent de plusieurs
+    let federated = results.iter().any(|r| r.container != results[0].container);
+
     egui::ScrollArea::vertical()
         .max_height(MAX_VISIBLE as f32 * RESULT_H)
         .auto_shrink([false, true])
@@ -76,6 +93,21 @@ pub fn show(
                                         .color(style::TEXT_PRIMARY)
                                         .strong(),
                                 );
+                                if let Some(lines) = line_range(result) {
+                                    ui.label(
+                                        egui::RichText::new(lines)
+                                            .size(10.0)
+                                            .color(style::TEXT_TERTIARY)
+                                            .monospace(),
+                                    );
+                                }
+                                if federated {
+                                    ui.label(
+                                        egui::RichText::new(format!("\u{25A0} {}", result.container))
+                                            .size(10.0)
+                                            .color(style::ACCENT),
+                                    );
+                                }
                                 ui.with_layout(
                                     egui::Layout::right_to_left(egui::Align::Center),
                                     |ui| {
@@ -9,11 +9,18 @@ pub enum SettingsAction {
     None,
     Close,
     SwitchContainer(String),
+    /// Search the container along with the active one, or stop. Leaves the panel open.
+    ToggleSearched(String),
     CreateContainer,
     DeleteContainer,
     ClearIndex,
     ReindexAll,
+    PruneIndex,
+    CheckIndex,
+    ExportContainer,
+    ImportContainer,
     AddFolder,
+    RemoveFolder(String),
     CycleLocale,
 }
 
@@ -65,23 +72,51 @@ pub fn show(
                 };
                 let suffix = if is_active { " [active]" } else { "" };
 
-                let resp = ui.add(
-                    egui::Button::new(
-                        egui::RichText::new(format!("\u{25A0} {}{}", container.name, suffix))
-                            .size(12.0)
-                            .color(text_color),
-                    )
-                    .fill(if is_active {
-                        style::FILL_CONTROL_HOVER
-                    } else {
-                        egui::Color32::TRANSPARENT
-                    })
-                    .frame(false),
-                );
-                if resp.clicked() && !is_active {
-                    action = SettingsAction::SwitchContainer(container.name.clone());
-                    close = true;
-                }
+                ui.horizontal(|ui| {
+                    let resp = ui.add(
+                        egui::Button::new(
+                            egui::RichText::new(format!("\u{25A0} {}{}", container.name, suffix))
+                                .size(12.0)
+                                .color(text_color),
+                        )
+                        .fill(if is_active {
+                            style::FILL_CONTROL_HOVER
+                        } else {
+                            egui::Color32::TRANSPARENT
+                        })
+                        .frame(false),
+                    );
+                    if resp.clicked() && !is_active {
+                        action = SettingsAction::SwitchContainer(container.name.clone());
+                        close = true;
+                    }
+
+                    // Case a cocher : chercher aussi dans ce conteneur
+                    if !is_active {
+                        ui.with_layout(
+                            egui::Layout::right_to_left(egui::Align::Center),
+                            |ui| {
+                                let (icon, color) = if container.searched {
+                                    ("\u{2611}", style::ACCENT)
+                                } else {
+                                    ("\u{2610}", style::TEXT_TERTIARY)
+                                };
+                                let toggle = ui
+                                    .add(
+                                        egui::Button::new(
+                                            egui::RichText::new(icon).size(12.0).color(color),
+                                        )
+                                        .fill(egui::Color32::TRANSPARENT)
+                                        .frame(false),
+                                    )
+                                    .on_hover_text(i18n::ts(locale, "settings_search_also"));
+                                if toggle.clicked() {
+                                    action = SettingsAction::ToggleSearched(container.name.clone());
+                                }
+                            },
+                        );
+                    }
+                });
             }
 
             // + Nouveau conteneur
@@ -137,12 +172,37 @@ pub fn show(
                             .rev()
                             .collect::<Vec<_>>()
                             .join("/");
-                        ui.label(
-                            egui::RichText::new(format!("\u{1F4C2} {}", short))
-                                .size(11.0)
-                                .color(style::TEXT_SECONDARY),
-                        )
-                        .on_hover_text(path.as_str());
+                        ui.horizontal(|ui| {
+                            ui.label(
+                                egui::RichText::new(format!("\u{1F4C2} {}", short))
+                                    .size(11.0)
+                                    .color(style::TEXT_SECONDARY),
+                            )
+                            .on_hover_text(path.as_str());
+
+                            // Retirer le dossier du conteneur (et ses lignes de l'index)
+                            ui.with_layout(
+                                egui::Layout::right_to_left(egui::Align::Center),
+                                |ui| {
+                                    let remove_btn = ui
+                                        .add_enabled(
+                                            !is_indexing,
+                                            egui::Button::new(
+                                                egui::RichText::new("\u{2715}")
+                                                    .size(11.0)
+                                                    .color(style::TEXT_TERTIARY),
+                                            )
+                                            .fill(egui::Color32::TRANSPARENT)
+                                            .frame(false),
+                                        )
+                                        .on_hover_text(i18n::ts(locale, "settings_remove_folder"));
+                                    if remove_btn.clicked() {
+                                        action = SettingsAction::RemoveFolder(path.clone());
+                                        close = true;
+                                    }
+                                },
+                            );
+                        });
                     }
                 }
             }
@@ -187,6 +247,81 @@ pub fn show(
                 close = true;
             }
 
+            // Retire de l'index les fichiers supprimes ou deplaces hors du watcher
+            let prune_btn = ui.add_enabled(
+                !is_indexing,
+                egui::Button::new(
+                    egui::RichText::new(format!(
+                        "\u{2702} {}",
+                        i18n::ts(locale, "sidebar_prune")
+                    ))
+                    .size(12.0)
+                    .color(style::TEXT_SECONDARY),
+                )
+                .fill(egui::Color32::TRANSPARENT)
+                .frame(false),
+            );
+            if prune_btn.clicked() {
+                action = SettingsAction::PruneIndex;
+                close = true;
+            }
+
+            // Tables orphelines, lignes hors des dossiers, vecteurs NaN, index manquants
+            let check_btn = ui.add_enabled(
+                !is_indexing,
+                egui::Button::new(
+                    egui::RichText::new(format!(
+                        "\u{2695} {}",
+                        i18n::ts(locale, "sidebar_check")
+                    ))
+                    .size(12.0)
+                    .color(style::TEXT_SECONDARY),
+                )
+                .fill(egui::Color32::TRANSPARENT)
+                .frame(false),
+            );
+            if check_btn.clicked() {
+                action = SettingsAction::CheckIndex;
+                close = true;
+            }
+
+            // Archive portable : index + dossiers + modele, sans re-embedding a l'arrivee
+            let export_btn = ui.add_enabled(
+                !is_indexing,
+                egui::Button::new(
+                    egui::RichText::new(format!(
+                        "\u{2197} {}",
+                        i18n::ts(locale, "sidebar_export")
+                    ))
+                    .size(12.0)
+                    .color(style::TEXT_SECONDARY),
+                )
+                .fill(egui::Color32::TRANSPARENT)
+                .frame(false),
+            );
+            if export_btn.clicked() {
+                action = SettingsAction::ExportContainer;
+                close = true;
+            }
+
+            let import_btn = ui.add_enabled(
+                !is_indexing,
+                egui::Button::new(
+                    egui::RichText::new(format!(
+                        "\u{2199} {}",
+                        i18n::ts(locale, "sidebar_import")
+                    ))
+                    .size(12.0)
+                    .color(style::TEXT_SECONDARY),
+                )
+                .fill(egui::Color32::TRANSPARENT)
+                .frame(false),
+            );
+            if import_btn.clicked() {
+                action = SettingsAction::ImportContainer;
+                close = true;
+            }
+
             let clear_btn = ui.add_enabled(
                 !is_indexing,
                 egui::Button::new(
@@ -1,20 +1,35 @@
 use eframe::egui;
 
 use crate::i18n::{self, Language};
+use crate::indexer::report::IndexReport;
+use crate::jobs::{JobId, JobInfo, JobState};
 use crate::state::IndexingProgress;
 
 use super::style;
 
+pub enum StatusAction {
+    None,
+    PauseJob(JobId),
+    ResumeJob(JobId),
+    CancelJob(JobId),
+}
+
 pub fn show(
     ui: &mut egui::Ui,
     status: &str,
     is_indexing: bool,
     index_progress: Option<&IndexingProgress>,
+    jobs: &[JobInfo],
+    report: Option<&IndexReport>,
     active_container: &str,
     folder_count: usize,
     result_count: usize,
     locale: Language,
-) {
+) -> StatusAction {
+    let mut action = StatusAction::None;
+    // Controles du job le plus recent (pause/reprise, annulation)
+    let job = jobs.last();
+
     let frame = egui::Frame::new()
         .fill(egui::Color32::from_rgba_premultiplied(15, 15, 15, 150))
         .inner_margin(egui::Margin { left: 12, right: 12, top: 0, bottom: 0 });
@@ -42,7 +57,18 @@ pub fn show(
                 );
 
                 // Statut ou nombre de dossiers/resultats
-                if !status.is_empty() {
+                if let Some(job) = job.filter(|j| j.state != JobState::Running) {
+                    let key = if job.state == JobState::Paused {
+                        "status_paused"
+                    } else {
+                        "status_cancelling"
+                    };
+                    ui.label(
+                        egui::RichText::new(i18n::ts(locale, key))
+                            .size(11.0)
+                            .color(style::TEXT_TERTIARY),
+                    );
+                } else if !status.is_empty() {
                     if is_indexing {
                         // Prefixe pourcentage si progression disponible
                         let pct_prefix = if let Some(p) = index_progress {
@@ -97,7 +123,100 @@ pub fn show(
                         );
                     }
                 }
+
+                // Fichiers ignores / en echec du dernier run, detail au survol
+                if let Some(report) = report.filter(|r| !is_indexing && has_issues(r)) {
+                    ui.label(
+                        egui::RichText::new(format!(
+                            "\u{26A0} {}",
+                            report.skipped_count() + report.failed_count()
+                        ))
+                        .size(11.0)
+                        .color(style::SCORE_ORANGE),
+                    )
+                    .on_hover_text(report_details(report, locale));
+                }
+
+                if let Some(job) = job.filter(|j| j.state != JobState::Cancelled) {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if job_button(ui, "\u{2715}", &i18n::ts(locale, "job_cancel")) {
+                            action = StatusAction::CancelJob(job.id);
+                        }
+                        if job.state == JobState::Paused {
+                            if job_button(ui, "\u{25B6}", &i18n::ts(locale, "job_resume")) {
+                                action = StatusAction::ResumeJob(job.id);
+                            }
+                        } else if job_button(ui, "\u{23F8}", &i18n::ts(locale, "job_pause")) {
+                            action = StatusAction::PauseJob(job.id);
+                        }
+                    });
+                }
             },
         );
     });
+
+    action
+}
+
+fn has_issues(report: &IndexReport) -> bool {
+    report.skipped_count() + report.failed_count() > 0
+}
+
+/// Texte du tooltip : resume, timings, puis les premiers fichiers ignores / en echec.
+fn report_details(report: &IndexReport, locale: Language) -> String {
+    const MAX_LISTED: usize = 12;
+
+    let mut lines = vec![
+        report.summary(),
+        i18n::t(
+            locale,
+            "report_timings",
+            &[
+                ("walk", &format_ms(report.timings.walk_ms)),
+                ("embed", &format_ms(report.timings.embed_ms)),
+                ("total", &format_ms(report.timings.total_ms)),
+            ],
+        ),
+    ];
+    for (key, issues, unlisted) in [
+        ("report_failed", &report.failed, report.failed_unlisted),
+        ("report_skipped", &report.skipped, report.skipped_unlisted),
+    ] {
+        if issues.is_empty() {
+            continue;
+        }
+        lines.push(String::new());
+        lines.push(i18n::ts(locale, key));
+        for issue in issues.iter().take(MAX_LISTED) {
+            let name = issue.path.rsplit(['/', '\\']).next().unwrap_or(&issue.path);
+            lines.push(format!("  {} -- {}", name, issue.reason));
+        }
+        let more = issues.len().saturating_sub(MAX_LISTED) + unlisted;
+        if more > 0 {
+            lines.push(i18n::t(locale, "report_more", &[("count", &more.to_string())]));
+        }
+    }
+    lines.join("\n")
+}
+
+fn format_ms(ms: u64) -> String {
+    if ms >= 1000 {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    } else {
+        format!("{}ms", ms)
+    }
+}
+
+fn job_button(ui: &mut egui::Ui, icon: &str, tooltip: &str) -> bool {
+    ui.add(
+        egui::Button::new(
+            egui::RichText::new(icon)
+                .size(11.0)
+                .color(style::TEXT_TERTIARY),
+        )
+        .fill(egui::Color32::TRANSPARENT)
+        .frame(false),
+    )
+    .on_hover_text(tooltip)
+    .clicked()
 }
@@ -1,15 +1,17 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 use std::time::Duration;
 
 use notify_debouncer_full::notify::{self, RecursiveMode};
 use notify_debouncer_full::{new_debouncer, DebounceEventResult, Debouncer, RecommendedCache};
 use tokio::sync::Mutex;
 
-use crate::config::{get_table_name, ConfigState};
+use crate::config::{get_table_name, ConfigState, IndexedRoot, VectorConfig};
 use crate::events::{AppEvent, EventSender};
 use crate::indexer;
+use crate::indexer::paths::PathMap;
+use crate::indexer::rules::GlobRules;
 use crate::state::ModelState;
 
 fn build_gitignore(roots: &[String]) -> Option<ignore::gitignore::Gitignore> {
@@ -30,6 +32,13 @@ fn build_gitignore(roots: &[String]) -> Option<ignore::gitignore::Gitignore> {
     builder.build().ok()
 }
 
+/// Held by each watcher batch while it writes, and by table maintenance, which must not
+/// compact under it. Shared across restarts so a batch of the previous watcher still counts.
+pub fn indexing_lock() -> &'static Mutex<()> {
+    static LOCK: OnceLock<Mutex<()>> = OnceLock::new();
+    LOCK.get_or_init(Default::default)
+}
+
 pub struct WatcherHandle {
     _debouncer: Debouncer<notify::RecommendedWatcher, RecommendedCache>,
 }
@@ -49,17 +58,18 @@ pub async fn restart(
 ) {
     let handle = {
         let config = config_state.config.lock().await;
-        let table_name = get_table_name(&config.active_container);
-        let paths = config
+        let container = config.active_container.clone();
+        let table_name = get_table_name(&container);
+        let (paths, globs, vectors) = config
             .containers
             .get(&config.active_container)
-            .map(|info| info.indexed_paths.clone())
+            .map(|info| (info.indexed_paths.clone(), info.globs.clone(), info.vectors.clone()))
             .unwrap_or_default();
         let use_git_history = config.indexing.use_git_history;
         let chunk_size = config.indexing.chunk_size;
         let chunk_overlap = config.indexing.chunk_overlap;
         drop(config);
-        start_watcher(paths, db, model_state, table_name, tx, use_git_history, chunk_size, chunk_overlap)
+        start_watcher(paths, &globs, vectors, db, model_state, container, table_name, tx, use_git_history, chunk_size, chunk_overlap)
     };
 
     let mut guard = watcher_state.lock().await;
@@ -67,18 +77,23 @@ pub async fn restart(
 }
 
 fn start_watcher(
-    paths: Vec<String>,
+    roots: Vec<IndexedRoot>,
+    globs: &[String],
+    vectors: VectorConfig,
     db: lancedb::Connection,
     model_state: Arc<Mutex<ModelState>>,
+    container: String,
     table_name: String,
     tx: EventSender,
     use_git_history: bool,
     chunk_size: Option<usize>,
     chunk_overlap: Option<usize>,
 ) -> Option<WatcherHandle> {
-    if paths.is_empty() {
+    if roots.is_empty() {
         return None;
     }
+    let paths: Vec<String> = roots.iter().map(|r| r.path.clone()).collect();
+    let path_map = PathMap::new(roots);
 
     let (notify_tx, notify_rx) = std::sync::mpsc::channel();
 
@@ -101,9 +116,16 @@ fn start_watcher(
     }
 
     let gitignore = build_gitignore(&paths);
+    // Mirrors the walker's overrides so a file skipped at index time stays skipped here
+    let rules = match GlobRules::build(&paths, globs) {
+        Ok(r) => r,
+        Err(e) => {
+            eprintln!("watcher: {}", e);
+            return None;
+        }
+    };
 
     let rt = tokio::runtime::Handle::current();
-    let indexing_lock = Arc::new(Mutex::new(()));
     std::thread::spawn(move || {
         while let Ok(events) = notify_rx.recv() {
             let mut changed: HashSet<PathBuf> = HashSet::new();
@@ -116,7 +138,7 @@ fn start_watcher(
                         for p in &event.paths {
                             let dominated = gitignore.as_ref().map_or(false, |gi| {
                                 gi.matched_path_or_any_parents(p, false).is_ignore()
-                            });
+                            }) || rules.is_excluded(p, false);
                             if p.is_file() && !dominated {
                                 changed.insert(p.clone());
                             }
@@ -126,7 +148,7 @@ fn start_watcher(
                         for p in &event.paths {
                             let dominated = gitignore.as_ref().map_or(false, |gi| {
                                 gi.matched_path_or_any_parents(p, false).is_ignore()
-                            });
+                            }) || rules.is_excluded(p, false);
                             if !dominated {
                                 deleted.insert(p.clone());
                             }
@@ -143,14 +165,16 @@ fn start_watcher(
             let db = db.clone();
             let ms = model_state.clone();
             let tn = table_name.clone();
+            let path_map = path_map.clone();
+            let vectors = vectors.clone();
+            let container = container.clone();
             let tx = tx.clone();
-            let lock = indexing_lock.clone();
             let changed: Vec<PathBuf> = changed.into_iter().collect();
             let deleted: Vec<PathBuf> = deleted.into_iter().collect();
             let total = changed.len() + deleted.len();
 
             rt.spawn(async move {
-                let _guard = lock.lock().await;
+                let _guard = indexing_lock().lock().await;
 
                 let _ = tx.send(AppEvent::IndexingProgress {
                     current: 0,
@@ -159,18 +183,27 @@ fn start_watcher(
                 });
 
                 let mut count = 0usize;
+                let mut reused = 0usize;
 
                 for path in &deleted {
-                    let path_str = path.to_string_lossy().to_string();
-                    if let Err(e) = indexer::delete_file_from_index(&path_str, &tn, &db).await {
+                    if let Err(e) = indexer::delete_file_from_index(path, &path_map, &tn, &db).await {
                         eprintln!("watcher: delete {:?}: {}", path, e);
                     }
                     count += 1;
                 }
 
                 for path in &changed {
-                    if let Err(e) = indexer::index_single_file(path, &tn, &db, &ms, use_git_history, chunk_size, chunk_overlap).await {
-                        eprintln!("watcher: index {:?}: {}", path, e);
+                    match indexer::index_single_file(path, &path_map, &tn, &db, &ms, &vectors, use_git_history, chunk_size, chunk_overlap).await {
+                        Ok(Some(stats)) => reused += stats.reused,
+                        Ok(None) => {}
+                        Err(e) => match indexer::schema::rebuild_reason(&e) {
+                            // Every other file would fail the same way
+                            Some(reason) => {
+                                let _ = tx.send(AppEvent::RebuildRequired { container, reason });
+                                break;
+                            }
+                            None => eprintln!("watcher: index {:?}: {}", path, e),
+                        },
                     }
                     count += 1;
                     let _ = tx.send(AppEvent::IndexingProgress {
@@ -181,8 +214,8 @@ fn start_watcher(
                 }
 
                 let _ = tx.send(AppEvent::IndexingComplete(format!(
-                    "{} files auto-reindexed",
-                    count
+                    "{} files auto-reindexed, {} chunks reused",
+                    count, reused
                 )));
             });
         }